@@ -1,12 +1,13 @@
-use crate::timer::Timer;
-use clap::Parser;
+use crate::{control::ControlCommand, duration::ClockTime, timer::Timer};
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::{Path, PathBuf};
 
 /// CLI arguments.
 #[derive(Debug, Parser)]
 #[clap(author, version, about, long_about = None)]
 pub struct Args {
-    /// Pomodoro duration.
+    /// Pomodoro duration (e.g. `25`, `25m`, `25:00`, `1h 30m`, `PT25M`).
     #[arg(short, long)]
     pomodoro: Option<Timer>,
     /// Short break duration.
@@ -18,12 +19,137 @@ pub struct Args {
     /// Pomodoros before long break.
     #[arg(short = 'n', long)]
     pomodoros: Option<u8>,
+    /// End the session after N cycles.
+    #[arg(long, value_name = "N")]
+    cycles: Option<u32>,
+    /// End the session after N completed pomodoros.
+    #[arg(long, value_name = "N")]
+    max_pomodoros: Option<u32>,
+    /// End the session at the given time of the day (e.g. `17:30`), once the running activity
+    /// is over.
+    #[arg(long, value_name = "HH:MM")]
+    until: Option<ClockTime>,
+    /// Task to work on (can be changed from the TUI with `t`).
+    #[arg(short, long)]
+    task: Option<String>,
     /// Custom configuration path.
     #[arg(short, long)]
     config: Option<PathBuf>,
+    /// Configuration profile, overriding the default one.
+    #[arg(short = 'P', long)]
+    profile: Option<String>,
     /// FIGlet font file.
     #[arg(short, long)]
     font: Option<PathBuf>,
+    /// Resume the session left by the previous run, unless older than `resume_cutoff`.
+    #[arg(short, long)]
+    resume: bool,
+    /// Run with no terminal UI (e.g. in the background), controlled through `solanum ctl`.
+    #[arg(long, visible_alias = "daemon")]
+    headless: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Subcommands, run instead of the timer.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print statistics out of the activity history.
+    Stats(StatsArgs),
+    /// Export the activity history to other tools.
+    Export(ExportArgs),
+    /// Control the running instance (e.g. from window manager keybindings).
+    Ctl(CtlArgs),
+    /// Print the status of the running instance, for status bars.
+    Status(StatusArgs),
+    /// Open the terminal UI of the running instance (e.g. started with `--headless`); press `q`
+    /// or `d` to detach, leaving it running until `solanum ctl quit`.
+    Attach,
+}
+
+/// Range of days to filter the activity history with.
+#[derive(Debug, Clone, Copy, clap::Args)]
+pub struct DateRange {
+    /// First day to include (e.g. `2022-11-01`).
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub since: Option<NaiveDate>,
+    /// Last day to include.
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub until: Option<NaiveDate>,
+}
+
+impl DateRange {
+    /// Whether `date` is in range.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.since.is_none_or(|since| date >= since) && self.until.is_none_or(|until| date <= until)
+    }
+}
+
+/// Period to group statistics by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Period {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+/// `stats` subcommand arguments.
+#[derive(Debug, clap::Args)]
+pub struct StatsArgs {
+    #[command(flatten)]
+    pub range: DateRange,
+    /// Period to group statistics by.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub by: Period,
+    /// Print statistics as JSON, for scripting.
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// `ctl` subcommand arguments.
+#[derive(Debug, clap::Args)]
+pub struct CtlArgs {
+    /// Command to send.
+    #[arg(value_enum)]
+    pub command: ControlCommand,
+}
+
+/// `status` subcommand arguments.
+#[derive(Debug, clap::Args)]
+pub struct StatusArgs {
+    /// Output template, replacing `{activity}`, `{remaining}`, `{progress}`, `{state}`, `{label}`
+    /// and `{task}`.
+    #[arg(short, long, default_value = "{activity} {remaining}")]
+    pub format: String,
+    /// Print waybar JSON (`text`, `tooltip`, `class`, `percentage`).
+    #[arg(short, long)]
+    pub waybar: bool,
+    /// Keep printing a line on every tick.
+    #[arg(short = 'F', long)]
+    pub follow: bool,
+}
+
+/// Format to export the activity history to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    /// iCalendar.
+    Ics,
+}
+
+/// `export` subcommand arguments.
+#[derive(Debug, clap::Args)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    pub range: DateRange,
+    /// Export format.
+    #[arg(short, long, value_enum)]
+    pub format: ExportFormat,
+    /// Output file, instead of standard output.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
 }
 
 impl Args {
@@ -56,15 +182,63 @@ impl Args {
         self.pomodoros
     }
 
+    /// Getter method for `cycles` filed.
+    #[inline]
+    pub fn get_cycles(&self) -> Option<u32> {
+        self.cycles
+    }
+
+    /// Getter method for `max_pomodoros` filed.
+    #[inline]
+    pub fn get_max_pomodoros(&self) -> Option<u32> {
+        self.max_pomodoros
+    }
+
+    /// Getter method for `until` filed.
+    #[inline]
+    pub fn get_until(&self) -> Option<ClockTime> {
+        self.until
+    }
+
+    /// Getter method for `task` field.
+    #[inline]
+    pub fn get_task(&self) -> Option<&str> {
+        self.task.as_deref()
+    }
+
     /// Getter method for `config` filed.
     #[inline]
     pub fn get_config_path(&self) -> Option<&Path> {
         self.config.as_deref()
     }
 
+    /// Getter method for `profile` field.
+    #[inline]
+    pub fn get_profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
     /// Getter method for `font` field.
     #[inline]
     pub fn get_font(&self) -> Option<&Path> {
         self.font.as_deref()
     }
+
+    /// Getter method for `resume` field.
+    #[inline]
+    pub fn get_resume(&self) -> bool {
+        self.resume
+    }
+
+    /// Getter method for `headless` field.
+    #[inline]
+    pub fn get_headless(&self) -> bool {
+        self.headless
+    }
+
+    /// Getter method for `command` field.
+    #[inline]
+    pub fn get_command(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
 }
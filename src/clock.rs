@@ -0,0 +1,186 @@
+use std::{
+    sync::mpsc::{Receiver, RecvError, RecvTimeoutError},
+    time::{Duration, Instant, SystemTime},
+};
+
+#[cfg(test)]
+use std::sync::{mpsc::TryRecvError, Arc, Mutex};
+
+/// Source of time for [`Timer`](crate::timer::Timer) and [`Session`](crate::session::Session).
+///
+/// Waiting for events is part of the clock too, so that a fake clock can make time pass
+/// instantly instead of blocking.
+pub trait Clock {
+    /// Current monotonic time.
+    fn now(&self) -> Instant;
+
+    /// Current wall-clock time.
+    fn wall(&self) -> SystemTime;
+
+    /// Wait for a message on `rx` for at most `timeout`.
+    fn recv_timeout<T>(&self, rx: &Receiver<T>, timeout: Duration) -> Result<T, RecvTimeoutError>;
+
+    /// Wait for a message on `rx` with no timeout.
+    fn recv<T>(&self, rx: &Receiver<T>) -> Result<T, RecvError>;
+}
+
+/// [`Clock`] backed by the system clocks.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    #[inline]
+    fn wall(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    #[inline]
+    fn recv_timeout<T>(&self, rx: &Receiver<T>, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        rx.recv_timeout(timeout)
+    }
+
+    #[inline]
+    fn recv<T>(&self, rx: &Receiver<T>) -> Result<T, RecvError> {
+        rx.recv()
+    }
+}
+
+/// Action scheduled on a [`ManualClock`].
+#[cfg(test)]
+type Action = Box<dyn FnOnce() + Send>;
+
+/// [`ManualClock`] shared state.
+#[cfg(test)]
+#[derive(Default)]
+struct ManualState {
+    /// Monotonic time elapsed since the clock was created.
+    elapsed: Duration,
+    /// Time spent in (simulated) system suspend, which only the wall-clock accounts for.
+    suspended: Duration,
+    /// Actions to run once `elapsed` reaches their time.
+    schedule: Vec<(Duration, Action)>,
+}
+
+/// Fake [`Clock`] for deterministic tests: time only passes while waiting for events, and does so
+/// instantly.
+///
+/// Events are injected by scheduling actions (e.g. sending an event or dropping the sender) at
+/// given points in time, which run on the waiting thread.
+#[cfg(test)]
+#[derive(Clone)]
+pub struct ManualClock {
+    /// Monotonic time at creation.
+    start: Instant,
+    /// Wall-clock time at creation.
+    start_wall: SystemTime,
+    state: Arc<Mutex<ManualState>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    /// Construct a new [`ManualClock`].
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            start_wall: SystemTime::now(),
+            state: Default::default(),
+        }
+    }
+
+    /// Monotonic time elapsed since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        self.state.lock().unwrap().elapsed
+    }
+
+    /// Schedule `action` to run once `at` has elapsed since the clock was created.
+    pub fn schedule(&self, at: Duration, action: impl FnOnce() + Send + 'static) {
+        self.state
+            .lock()
+            .unwrap()
+            .schedule
+            .push((at, Box::new(action)));
+    }
+
+    /// Simulate a system suspend of `duration`: only the wall-clock moves forward.
+    pub fn suspend(&self, duration: Duration) {
+        self.state.lock().unwrap().suspended += duration;
+    }
+
+    /// Move time forward to the next action scheduled no later than `until` and run it.
+    ///
+    /// Return `false` if no such action exists, moving time forward to `until` instead (if any).
+    fn run_next(&self, until: Option<Duration>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let next = state
+            .schedule
+            .iter()
+            .enumerate()
+            .filter(|(_, (at, _))| until.is_none_or(|until| *at <= until))
+            .min_by_key(|(_, (at, _))| *at)
+            .map(|(i, _)| i);
+
+        match next {
+            Some(i) => {
+                let (at, action) = state.schedule.remove(i);
+                state.elapsed = state.elapsed.max(at);
+                // Run action without holding the lock, since it may use the clock.
+                drop(state);
+                action();
+                true
+            }
+            None => {
+                if let Some(until) = until {
+                    state.elapsed = state.elapsed.max(until);
+                }
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn wall(&self) -> SystemTime {
+        let state = self.state.lock().unwrap();
+        self.start_wall + state.elapsed + state.suspended
+    }
+
+    fn recv_timeout<T>(&self, rx: &Receiver<T>, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let until = self.elapsed() + timeout;
+        loop {
+            match rx.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {
+                    if !self.run_next(Some(until)) {
+                        return Err(RecvTimeoutError::Timeout);
+                    }
+                }
+            }
+        }
+    }
+
+    fn recv<T>(&self, rx: &Receiver<T>) -> Result<T, RecvError> {
+        loop {
+            match rx.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                // Nothing left to happen: block on the channel.
+                Err(TryRecvError::Empty) => {
+                    if !self.run_next(None) {
+                        return rx.recv();
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,14 +1,28 @@
 use crate::{
     args::Args,
     error::Error,
-    session::Session,
+    figlet::Font,
+    session::{ActivityKind, Session},
     ui::{Ui, UiOptions},
-    Result, figlet::Font,
+    Result,
 };
 use serde::Deserialize;
 use std::{fs, path::Path};
+use toml::{value::Table, Value};
 
 /// Configuration options.
+///
+/// Named profiles (`[profiles.<name>]` tables) hold partial configurations overriding the
+/// top-level one, e.g.:
+///
+/// ```toml
+/// # Profile used when `--profile` is not given.
+/// profile = "deep"
+///
+/// [profiles.deep.session]
+/// pomodoro = "52m"
+/// short_break = "17m"
+/// ```
 #[derive(Debug, Deserialize, Default)]
 pub struct Config {
     /// Ui configuration options.
@@ -20,8 +34,9 @@ pub struct Config {
 }
 
 impl Config {
-    /// Parse configuration from configuration file.
-    pub fn new(config_path: Option<&Path>) -> Result<Self> {
+    /// Parse configuration from configuration file, applying `profile` (or the default profile,
+    /// if any).
+    pub fn new(config_path: Option<&Path>, profile: Option<&str>) -> Result<Self> {
         let config_path = match config_path {
             Some(path) => {
                 // Argument configuration file path does not exist.
@@ -32,14 +47,41 @@ impl Config {
             }
             None => match dirs::config_dir() {
                 Some(config_path) => config_path.join("solanum/config.toml"),
-                None => return Ok(Self::default()),
+                None => return Self::parse("", profile),
             },
         };
 
-        Ok(match fs::read_to_string(config_path) {
-            Ok(config) => toml::from_str(&config)?,
-            Err(_) => Self::default(),
-        })
+        Self::parse(
+            &fs::read_to_string(config_path).unwrap_or_default(),
+            profile,
+        )
+    }
+
+    /// Parse configuration from `config` string, applying `profile` (or the default profile, if
+    /// any).
+    fn parse(config: &str, profile: Option<&str>) -> Result<Self> {
+        let mut config: Table = toml::from_str(config)?;
+
+        let default_profile = match config.remove("profile") {
+            Some(Value::String(profile)) => Some(profile),
+            Some(_) => return Err(Error::Profile("`profile` must be a string".to_string())),
+            None => None,
+        };
+        let mut profiles = match config.remove("profiles") {
+            Some(Value::Table(profiles)) => profiles,
+            Some(_) => return Err(Error::Profile("`profiles` must be a table".to_string())),
+            None => Table::new(),
+        };
+
+        if let Some(name) = profile.or(default_profile.as_deref()) {
+            match profiles.remove(name) {
+                Some(Value::Table(overrides)) => merge(&mut config, overrides),
+                Some(_) => return Err(Error::Profile(format!("`{name}` must be a table"))),
+                None => return Err(Error::Profile(format!("`{name}` not found"))),
+            }
+        }
+
+        Ok(Value::Table(config).try_into()?)
     }
 
     /// Override configuration with CLI arguments.
@@ -57,9 +99,40 @@ impl Config {
         }
 
         if let Some(pomodoros) = args.get_pomodoros() {
+            if !self.session.sequence.is_empty() {
+                return Err("`--pomodoros` doesn't apply to a custom `sequence`".into());
+            }
             self.session.pomodoros = pomodoros;
         }
 
+        // Durations apply to the steps of a custom sequence too.
+        for step in &mut self.session.sequence {
+            let duration = match step.kind {
+                ActivityKind::Pomodoro => args.get_pomodoro(),
+                ActivityKind::ShortBreak => args.get_short_break(),
+                ActivityKind::LongBreak => args.get_long_break(),
+            };
+            if let Some(duration) = duration {
+                step.duration = duration;
+            }
+        }
+
+        if let Some(cycles) = args.get_cycles() {
+            self.session.cycles = Some(cycles);
+        }
+
+        if let Some(pomodoros) = args.get_max_pomodoros() {
+            self.session.max_pomodoros = Some(pomodoros);
+        }
+
+        if let Some(until) = args.get_until() {
+            self.session.until = Some(until);
+        }
+
+        if let Some(task) = args.get_task() {
+            self.session.task = Some(task.to_string());
+        }
+
         if let Some(path) = args.get_font() {
             dbg!(&path);
             self.ui_options.font = Font::parse_flf(path)?;
@@ -73,3 +146,89 @@ impl Config {
         (self.session, Ui::new(self.ui_options))
     }
 }
+
+/// Merge `overrides` into `table`: nested tables are merged as well, while any other value
+/// (arrays included) is replaced.
+fn merge(table: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(&key), value) {
+            (Some(Value::Table(table)), Value::Table(overrides)) => merge(table, overrides),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::Parser;
+
+    const CONFIG: &str = r#"
+        profile = "study"
+
+        [session]
+        pomodoro = "25m"
+        short_break = "5m"
+
+        [profiles.study.session]
+        pomodoro = "45m"
+
+        [profiles.deep.session]
+        pomodoro = "90m"
+        short_break = "20m"
+    "#;
+
+    #[test]
+    /// Profiles overriding the top-level configuration.
+    fn config_profiles() {
+        let config = Config::parse(CONFIG, None).unwrap();
+        assert_eq!(config.session.pomodoro.to_string(), "45m");
+        assert_eq!(config.session.short_break.to_string(), "5m");
+
+        let config = Config::parse(CONFIG, Some("deep")).unwrap();
+        assert_eq!(config.session.pomodoro.to_string(), "1h30m");
+        assert_eq!(config.session.short_break.to_string(), "20m");
+
+        let config = Config::parse("", None).unwrap();
+        assert_eq!(config.session.pomodoro.to_string(), "25m");
+
+        assert_eq!(
+            Config::parse(CONFIG, Some("meeting"))
+                .unwrap_err()
+                .to_string(),
+            "invalid profile: `meeting` not found"
+        );
+    }
+
+    #[test]
+    /// CLI durations applied to the steps of a custom sequence.
+    fn config_override_sequence() {
+        const SEQUENCE: &str = r#"
+            [[session.sequence]]
+            kind = "pomodoro"
+            duration = "50m"
+
+            [[session.sequence]]
+            kind = "short_break"
+            duration = "10m"
+        "#;
+        let args = |args: &[&str]| Args::parse_from([&["solanum"], args].concat());
+
+        let config = Config::parse(SEQUENCE, None)
+            .unwrap()
+            .override_with_args(args(&["-p", "30m"]))
+            .unwrap();
+        let durations: Vec<_> = config
+            .session
+            .sequence
+            .iter()
+            .map(|step| step.duration.to_string())
+            .collect();
+        assert_eq!(durations, ["30m", "10m"]);
+
+        let config = Config::parse(SEQUENCE, None).unwrap();
+        assert!(config.override_with_args(args(&["-n", "2"])).is_err());
+    }
+}
@@ -0,0 +1,551 @@
+use crate::{
+    error::Error,
+    event::{Event, EventHandler},
+    session::Activity,
+    timer::TimerStatus,
+    ui::{Ui, UiCommand},
+    Result,
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    mem,
+    net::Shutdown,
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// Request streaming [`Status`] updates, as sent over the socket.
+const FOLLOW: &str = "follow";
+
+/// Request streaming [`UiCommand`]s while sending [`Event`]s back, as sent over the socket.
+const ATTACH: &str = "attach";
+
+/// Path of the control socket, in the XDG runtime directory (if any).
+pub fn socket_path() -> Option<PathBuf> {
+    dirs::runtime_dir().map(|dir| dir.join("solanum.sock"))
+}
+
+/// Commands accepted by the control socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ControlCommand {
+    /// Pause the running timer.
+    Pause,
+    /// Resume the paused timer (or start the next activity, if waiting).
+    Resume,
+    /// Toggle pause.
+    Toggle,
+    /// Skip the running activity.
+    Skip,
+    /// Print the status of the running timer, as JSON.
+    Status,
+    /// End the session.
+    Quit,
+}
+
+impl ControlCommand {
+    /// Name of the command, as sent over the socket.
+    pub fn name(&self) -> String {
+        self.to_possible_value().unwrap().get_name().to_string()
+    }
+}
+
+/// State of the timer reported by [`Status`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerState {
+    #[default]
+    Running,
+    Paused,
+    Overtime,
+    Expired,
+    /// Waiting for the user to start `activity`.
+    Waiting,
+}
+
+/// Status of the running session, as reported by the control socket.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Status {
+    pub state: TimerState,
+    /// Current activity (or next one, if waiting).
+    pub activity: Option<Activity>,
+    /// Remaining time, in seconds.
+    pub remaining: u64,
+    /// Timer duration, in seconds.
+    pub duration: u64,
+    /// Time spent after the timer expired, in seconds.
+    pub overtime: u64,
+    /// Label of the current sequence step.
+    pub label: Option<String>,
+    /// Task being worked on.
+    pub task: Option<String>,
+}
+
+impl Status {
+    /// Update status according to `ui_command` sent by the session.
+    fn update(&mut self, ui_command: &UiCommand) {
+        match ui_command {
+            UiCommand::Draw(status) => {
+                let (state, activity, timer) = match status {
+                    TimerStatus::Running(activity, timer) => {
+                        (TimerState::Running, Some(*activity), Some(timer))
+                    }
+                    TimerStatus::Paused(activity, timer, _) => {
+                        (TimerState::Paused, Some(*activity), Some(timer))
+                    }
+                    TimerStatus::Overtime(activity, overtime) => {
+                        self.overtime = overtime.duration().as_secs();
+                        (TimerState::Overtime, Some(*activity), None)
+                    }
+                    TimerStatus::Expired => (TimerState::Expired, self.activity, None),
+                    TimerStatus::Waiting(next) => (TimerState::Waiting, Some(*next), None),
+                };
+                if activity != self.activity {
+                    self.overtime = 0;
+                }
+                self.state = state;
+                self.activity = activity;
+                self.remaining = timer.map_or(0, |timer| timer.remaining().as_secs());
+                if let Some(timer) = timer {
+                    self.duration = timer.duration().as_secs();
+                }
+            }
+            UiCommand::Step { label, .. } => self.label = label.clone(),
+            UiCommand::Task(task) => self.task = task.clone(),
+            _ => {}
+        }
+    }
+}
+
+/// Session [`Status`] shared between the relay thread and the clients of the control socket.
+#[derive(Debug, Default)]
+pub struct Hub {
+    /// Current status.
+    status: Mutex<Status>,
+    /// Clients following status updates.
+    followers: Mutex<Vec<Sender<Status>>>,
+    /// Last [`UiCommand`] of each kind, bringing attached clients up to date.
+    snapshot: Mutex<Vec<UiCommand>>,
+    /// Attached clients.
+    attached: Mutex<Vec<Sender<UiCommand>>>,
+}
+
+impl Hub {
+    /// Current status.
+    fn status(&self) -> Status {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Update status according to `ui_command`, sending it to followers if changed, and send
+    /// `ui_command` to attached clients.
+    fn update(&self, ui_command: &UiCommand) {
+        let mut snapshot = self.snapshot.lock().unwrap();
+        // Notices are only relevant to the activity they were sent for.
+        if !matches!(ui_command, UiCommand::Notice(_)) {
+            snapshot.retain(|last| mem::discriminant(last) != mem::discriminant(ui_command));
+            snapshot.push(ui_command.clone());
+        }
+        self.attached
+            .lock()
+            .unwrap()
+            .retain(|client| client.send(ui_command.clone()).is_ok());
+
+        let mut status = self.status.lock().unwrap();
+        let previous = status.clone();
+        status.update(ui_command);
+        if *status != previous {
+            // Followers gone are forgotten.
+            self.followers
+                .lock()
+                .unwrap()
+                .retain(|follower| follower.send(status.clone()).is_ok());
+        }
+    }
+
+    /// Follow status updates, starting from the current status.
+    fn follow(&self) -> Receiver<Status> {
+        let (tx, rx) = mpsc::channel();
+        // Status can't change meanwhile.
+        let status = self.status.lock().unwrap();
+        tx.send(status.clone()).unwrap();
+        self.followers.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Attach a client, receiving every [`UiCommand`] once brought up to date.
+    fn attach(&self) -> Receiver<UiCommand> {
+        let (tx, rx) = mpsc::channel();
+        // Snapshot can't change meanwhile.
+        let snapshot = self.snapshot.lock().unwrap();
+        for ui_command in snapshot.iter() {
+            tx.send(ui_command.clone()).unwrap();
+        }
+        self.attached.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Disconnect followers and attached clients, once the session is over.
+    fn close(&self) {
+        self.followers.lock().unwrap().clear();
+        self.attached.lock().unwrap().clear();
+    }
+}
+
+/// Spawn thread relaying [`UiCommand`]s from the session to the Ui, keeping `hub` up to date
+/// meanwhile.
+///
+/// Followers and attached clients are disconnected once the session is over.
+pub fn spawn_relay(
+    rx: Receiver<UiCommand>,
+    tx: Sender<UiCommand>,
+    hub: Arc<Hub>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        for ui_command in rx {
+            hub.update(&ui_command);
+            // Status is still tracked, should the Ui be gone.
+            let _ = tx.send(ui_command);
+        }
+        hub.close();
+    })
+}
+
+/// Control socket server, mapping commands onto [`Event`]s.
+///
+/// The socket file is removed once dropped.
+#[derive(Debug)]
+pub struct Server {
+    /// Socket path.
+    path: PathBuf,
+}
+
+impl Server {
+    /// Listen on socket at `path`, sending [`Event`]s to `tx_event` and reporting the status
+    /// kept by `hub`.
+    pub fn bind(path: PathBuf, tx_event: Sender<Event>, hub: Arc<Hub>) -> Result<Self> {
+        let error = |err: &dyn ToString| Error::Socket(path.clone(), err.to_string());
+        if path.exists() {
+            // Stale socket left by a crashed instance can be replaced.
+            if UnixStream::connect(&path).is_ok() {
+                return Err(error(&"already in use by another instance"));
+            }
+            fs::remove_file(&path).map_err(|err| error(&err))?;
+        }
+        let listener = UnixListener::bind(&path).map_err(|err| error(&err))?;
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let tx_event = tx_event.clone();
+                let hub = Arc::clone(&hub);
+                thread::spawn(move || handle_client(stream, tx_event, hub));
+            }
+        });
+
+        Ok(Self { path })
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Serialize `status` as a JSON line.
+fn to_json(status: &Status) -> Result<String> {
+    serde_json::to_string(status).map_err(|err| Error::Other(err.to_string()))
+}
+
+/// Serve commands sent by a client, one per line, answering each with one line.
+///
+/// Following clients get one line per status update instead, until the session is over, while
+/// attached clients get one line per [`UiCommand`] and send one line per [`Event`].
+fn handle_client(stream: UnixStream, tx_event: Sender<Event>, hub: Arc<Hub>) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut lines = BufReader::new(stream).lines();
+    while let Some(line) = lines.next() {
+        let line = line?;
+        if line.trim() == FOLLOW {
+            for status in hub.follow() {
+                writeln!(writer, "{}", to_json(&status)?)?;
+            }
+            return Ok(());
+        }
+
+        if line.trim() == ATTACH {
+            let rx = hub.attach();
+            thread::spawn(move || -> Result<()> {
+                for ui_command in rx {
+                    let line = serde_json::to_string(&ui_command)
+                        .map_err(|err| Error::Other(err.to_string()))?;
+                    writeln!(writer, "{line}")?;
+                }
+                // Session over: let the client know.
+                writer.shutdown(Shutdown::Both)?;
+                Ok(())
+            });
+            // Until the client detaches.
+            for line in lines {
+                let event =
+                    serde_json::from_str(&line?).map_err(|err| Error::Other(err.to_string()))?;
+                if tx_event.send(event).is_err() {
+                    break;
+                }
+            }
+            return Ok(());
+        }
+
+        let reply = match ControlCommand::from_str(line.trim(), true) {
+            Ok(ControlCommand::Status) => to_json(&hub.status())?,
+            Ok(command) => {
+                let event = match command {
+                    ControlCommand::Pause => Event::Pause,
+                    ControlCommand::Resume => Event::Resume,
+                    ControlCommand::Toggle => Event::TogglePause,
+                    ControlCommand::Skip => Event::Skip,
+                    ControlCommand::Quit => Event::Quit,
+                    ControlCommand::Status => unreachable!(),
+                };
+                match tx_event.send(event) {
+                    Ok(()) => "ok".to_string(),
+                    Err(_) => "error: session is over".to_string(),
+                }
+            }
+            Err(_) => format!("error: unknown command `{}`", line.trim()),
+        };
+        writeln!(writer, "{reply}")?;
+    }
+
+    Ok(())
+}
+
+/// Send `command` to the server listening at `path`, returning its reply.
+pub fn request(path: &Path, command: ControlCommand) -> Result<String> {
+    let error = |err: &dyn ToString| Error::Socket(path.to_path_buf(), err.to_string());
+    let mut stream = UnixStream::connect(path).map_err(|err| error(&err))?;
+    writeln!(stream, "{}", command.name()).map_err(|err| error(&err))?;
+
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .map_err(|err| error(&err))?;
+    match reply.trim_end().strip_prefix("error: ") {
+        Some(err) => Err(error(&err)),
+        None => Ok(reply.trim_end().to_string()),
+    }
+}
+
+/// Follow status updates of the server listening at `path`, passing each to `on_status` until
+/// the session is over.
+pub fn follow(path: &Path, mut on_status: impl FnMut(Status) -> Result<()>) -> Result<()> {
+    let error = |err: &dyn ToString| Error::Socket(path.to_path_buf(), err.to_string());
+    let mut stream = UnixStream::connect(path).map_err(|err| error(&err))?;
+    writeln!(stream, "{FOLLOW}").map_err(|err| error(&err))?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line.map_err(|err| error(&err))?;
+        on_status(serde_json::from_str(&line).map_err(|err| error(&err))?)?;
+    }
+
+    Ok(())
+}
+
+/// Attach to the session hosted by the server listening at `path`, rendering it with `ui` and
+/// sending it the user's events, until detached or the session is over.
+pub fn attach(path: &Path, ui: Ui) -> Result<()> {
+    let error = |err: &dyn ToString| Error::Socket(path.to_path_buf(), err.to_string());
+    let stream = UnixStream::connect(path).map_err(|err| error(&err))?;
+    let mut writer = stream.try_clone().map_err(|err| error(&err))?;
+    writeln!(writer, "{ATTACH}").map_err(|err| error(&err))?;
+
+    let (tx_ui, rx_ui) = mpsc::channel();
+    let (tx_event, rx_event) = mpsc::channel();
+    // Channel to shut EventHandler down, once disconnected (i.e. the session is over).
+    let (tx_shutdown, rx_shutdown) = mpsc::channel::<()>();
+
+    // Receive Ui commands from the session.
+    let receiver_thread = {
+        let tx_ui = tx_ui.clone();
+        let path = path.to_path_buf();
+        thread::spawn(move || -> Result<()> {
+            let _tx_shutdown = tx_shutdown;
+            for line in BufReader::new(stream).lines() {
+                let line = line.map_err(|err| Error::Socket(path.clone(), err.to_string()))?;
+                let ui_command = serde_json::from_str(&line)
+                    .map_err(|err| Error::Socket(path.clone(), err.to_string()))?;
+                if tx_ui.send(ui_command).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        })
+    };
+    let event_handler_thread = EventHandler::spawn_thread(tx_event, tx_ui, rx_shutdown, true);
+    let renderer_thread = ui.spawn_thread(rx_ui)?;
+
+    // Send events to the session.
+    for event in rx_event {
+        if event == Event::Detach {
+            break;
+        }
+        let line = serde_json::to_string(&event).map_err(|err| error(&err))?;
+        // Session over.
+        if writeln!(writer, "{line}").is_err() {
+            break;
+        }
+    }
+    // Leave the session running.
+    let _ = writer.shutdown(Shutdown::Both);
+
+    renderer_thread.join().unwrap()?;
+    event_handler_thread.join().unwrap()?;
+    receiver_thread.join().unwrap()?;
+
+    Ok(())
+}
+
+/// Send `command` to the running instance, printing its reply.
+pub fn ctl(command: ControlCommand) -> Result<()> {
+    let path = socket_path().ok_or("unable to locate runtime directory")?;
+    let reply = request(&path, command)?;
+    // Plain acknowledgments are not worth printing.
+    if command == ControlCommand::Status {
+        println!("{reply}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::timer::Timer;
+
+    #[test]
+    /// Commands sent over the socket mapped onto events, status reported.
+    fn control_socket() {
+        let path = std::env::temp_dir().join(format!("solanum-{}.sock", std::process::id()));
+        let (tx_event, rx_event) = mpsc::channel();
+        let hub = Arc::new(Hub::default());
+        let server = Server::bind(path.clone(), tx_event, Arc::clone(&hub)).unwrap();
+
+        // Only one instance at a time.
+        let (tx, _rx) = mpsc::channel();
+        assert!(Server::bind(path.clone(), tx, Arc::clone(&hub)).is_err());
+
+        assert_eq!(request(&path, ControlCommand::Pause).unwrap(), "ok");
+        assert_eq!(rx_event.recv().unwrap(), Event::Pause);
+        assert_eq!(request(&path, ControlCommand::Skip).unwrap(), "ok");
+        assert_eq!(rx_event.recv().unwrap(), Event::Skip);
+
+        let timer = Timer::new(0, 25, 0);
+        let paused = UiCommand::Draw(TimerStatus::Paused(
+            Activity::Pomodoro(1),
+            timer,
+            Timer::default(),
+        ));
+        hub.update(&paused);
+        let reply = request(&path, ControlCommand::Status).unwrap();
+        let reported: Status = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reported.state, TimerState::Paused);
+        assert_eq!(reported.activity, Some(Activity::Pomodoro(1)));
+        assert_eq!(reported.remaining, 25 * 60);
+
+        // Session over.
+        drop(rx_event);
+        assert!(request(&path, ControlCommand::Resume).is_err());
+
+        // Followers get the current status, then every update until the session is over.
+        let (tx_ui, rx_ui) = mpsc::channel();
+        let (tx_relay, rx_relay) = mpsc::channel();
+        let relay = spawn_relay(rx_relay, tx_ui, Arc::clone(&hub));
+        let follower = {
+            let path = path.clone();
+            thread::spawn(move || {
+                let mut states = vec![];
+                follow(&path, |status| {
+                    states.push(status.state);
+                    Ok(())
+                })
+                .unwrap();
+                states
+            })
+        };
+        while hub.followers.lock().unwrap().is_empty() {
+            thread::yield_now();
+        }
+        let running = TimerStatus::Running(Activity::Pomodoro(1), timer);
+        tx_relay.send(UiCommand::Draw(running.clone())).unwrap();
+        // Unchanged status is not sent again.
+        tx_relay.send(UiCommand::Draw(running)).unwrap();
+        tx_relay
+            .send(UiCommand::Draw(TimerStatus::Expired))
+            .unwrap();
+        drop(tx_relay);
+        relay.join().unwrap();
+        assert_eq!(rx_ui.iter().count(), 3);
+        assert_eq!(
+            follower.join().unwrap(),
+            [TimerState::Paused, TimerState::Running, TimerState::Expired]
+        );
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    /// Attached clients brought up to date, then receiving Ui commands and sending events.
+    fn control_attach() {
+        let path = std::env::temp_dir().join(format!("solanum-attach-{}.sock", std::process::id()));
+        let (tx_event, rx_event) = mpsc::channel();
+        let hub = Arc::new(Hub::default());
+        let _server = Server::bind(path.clone(), tx_event, Arc::clone(&hub)).unwrap();
+        let (tx_ui, _rx_ui) = mpsc::channel();
+        let (tx_relay, rx_relay) = mpsc::channel();
+        let relay = spawn_relay(rx_relay, tx_ui, Arc::clone(&hub));
+
+        let running = |secs| TimerStatus::Running(Activity::Pomodoro(1), Timer::new(0, 0, secs));
+        tx_relay
+            .send(UiCommand::Task(Some("Write report".to_string())))
+            .unwrap();
+        tx_relay.send(UiCommand::Draw(running(60))).unwrap();
+        tx_relay.send(UiCommand::Draw(running(59))).unwrap();
+        while hub.snapshot.lock().unwrap().len() < 2 {
+            thread::yield_now();
+        }
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        writeln!(stream, "{ATTACH}").unwrap();
+        let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+        let mut next =
+            || -> UiCommand { serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap() };
+        // Only the last command of each kind.
+        assert!(matches!(next(), UiCommand::Task(Some(task)) if task == "Write report"));
+        assert!(
+            matches!(next(), UiCommand::Draw(TimerStatus::Running(_, timer)) if timer.remaining().as_secs() == 59)
+        );
+
+        writeln!(stream, "{}", serde_json::to_string(&Event::Skip).unwrap()).unwrap();
+        assert_eq!(rx_event.recv().unwrap(), Event::Skip);
+        while hub.attached.lock().unwrap().is_empty() {
+            thread::yield_now();
+        }
+        tx_relay
+            .send(UiCommand::Draw(TimerStatus::Expired))
+            .unwrap();
+        assert!(matches!(next(), UiCommand::Draw(TimerStatus::Expired)));
+
+        // Session over: client disconnected.
+        drop(tx_relay);
+        relay.join().unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        assert!(lines.next().is_none());
+    }
+}
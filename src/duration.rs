@@ -0,0 +1,431 @@
+use crate::{error::Error, Result};
+use chrono::{DateTime, Days, Local, NaiveTime, TimeZone};
+use serde::{Deserialize, Deserializer};
+use std::{str::FromStr, time::SystemTime};
+
+/// Seconds per hour, minute and second, in the order units must appear.
+const UNITS: [(char, u128); 3] = [('h', 3600), ('m', 60), ('s', 1)];
+
+/// Parse duration from string, returning the number of seconds.
+///
+/// Accepted formats:
+/// - `25`, `1.5`: number of minutes;
+/// - `25:00`, `1:30:00`: `M:SS` or `H:MM:SS`;
+/// - `1h30m`, `1h 30m`, `1.5h`, `90s`: values with `h`, `m` or `s` units, in this order;
+/// - `PT25M`, `PT1H30M`: ISO-8601 duration (time components only).
+///
+/// Fractional values are rounded to the nearest second.
+pub fn parse_duration(s: &str) -> Result<usize> {
+    Parser::new(s).parse()
+}
+
+/// Time of the day (e.g. `17:30`), in local time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockTime(NaiveTime);
+
+impl ClockTime {
+    /// First occurrence of this time of the day after `now`: today, or tomorrow if already
+    /// past.
+    pub fn next_after(&self, now: SystemTime) -> SystemTime {
+        let now: DateTime<Local> = now.into();
+        let mut date = now.date_naive();
+        if date.and_time(self.0) <= now.naive_local() {
+            date = date + Days::new(1);
+        }
+
+        // Times skipped by DST transitions fall back to the following hour.
+        let datetime = date.and_time(self.0);
+        Local
+            .from_local_datetime(&datetime)
+            .earliest()
+            .or_else(|| {
+                Local
+                    .from_local_datetime(&(datetime + chrono::Duration::hours(1)))
+                    .earliest()
+            })
+            .map_or(SystemTime::from(now), SystemTime::from)
+    }
+}
+
+impl FromStr for ClockTime {
+    type Err = Error;
+
+    /// Parse [`ClockTime`] from `HH:MM` or `HH:MM:SS` string.
+    fn from_str(s: &str) -> Result<Self> {
+        NaiveTime::parse_from_str(s.trim(), "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(s.trim(), "%H:%M"))
+            .map(Self)
+            .map_err(|_| {
+                Error::ParseClockTime(format!("expected `HH:MM` or `HH:MM:SS`, got `{s}`"))
+            })
+    }
+}
+
+impl<'de> Deserialize<'de> for ClockTime {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Decimal number, kept as integers to avoid floating point rounding.
+#[derive(Debug, Clone, Copy, Default)]
+struct Number {
+    /// Integer part.
+    int: u128,
+    /// Fractional part digits.
+    frac: u128,
+    /// Number of fractional part digits.
+    frac_digits: u32,
+}
+
+impl Number {
+    /// Multiply by `unit` seconds, rounding to the nearest second.
+    fn seconds(&self, unit: u128) -> Result<u128> {
+        let overflow = || Error::TimerOverflow;
+        let scale = 10u128.checked_pow(self.frac_digits).ok_or_else(overflow)?;
+        let frac = self
+            .frac
+            .checked_mul(unit)
+            .map(|frac| (frac + scale / 2) / scale)
+            .ok_or_else(overflow)?;
+
+        self.int
+            .checked_mul(unit)
+            .and_then(|int| int.checked_add(frac))
+            .ok_or_else(overflow)
+    }
+}
+
+/// Duration parser keeping track of the position in the input, for error reporting.
+struct Parser<'a> {
+    input: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            chars: input.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    /// Construct [`Error::ParseTimer`] pointing at `pos`.
+    fn error_at(&self, pos: usize, msg: impl AsRef<str>) -> Error {
+        Error::ParseTimer(format!(
+            "{} at position {} in `{}`",
+            msg.as_ref(),
+            pos + 1,
+            self.input
+        ))
+    }
+
+    /// Construct [`Error::ParseTimer`] pointing at current position.
+    fn error(&self, msg: impl AsRef<str>) -> Error {
+        match self.peek() {
+            Some(c) => self.error_at(self.pos, format!("unexpected `{c}`, {}", msg.as_ref())),
+            None => self.error_at(self.pos, format!("unexpected end, {}", msg.as_ref())),
+        }
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    #[inline]
+    fn is_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Parse digits into integer, returning it along with the number of digits.
+    fn digits(&mut self) -> Result<(u128, u32)> {
+        let (mut value, mut count) = (0u128, 0);
+        while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+            value = value
+                .checked_mul(10)
+                .and_then(|value| value.checked_add(digit.into()))
+                .ok_or(Error::TimerOverflow)?;
+            count += 1;
+            self.pos += 1;
+        }
+
+        Ok((value, count))
+    }
+
+    /// Parse integer.
+    fn integer(&mut self) -> Result<u128> {
+        match self.digits()? {
+            (_, 0) => Err(self.error("expected a number")),
+            (value, _) => Ok(value),
+        }
+    }
+
+    /// Parse decimal number (e.g. `25`, `1.5`).
+    fn number(&mut self) -> Result<Number> {
+        let int = self.integer()?;
+        if self.peek() != Some('.') {
+            return Ok(Number {
+                int,
+                ..Default::default()
+            });
+        }
+
+        self.pos += 1;
+        match self.digits()? {
+            (_, 0) => Err(self.error("expected digits after `.`")),
+            (frac, frac_digits) => Ok(Number {
+                int,
+                frac,
+                frac_digits,
+            }),
+        }
+    }
+
+    /// Parse unit among `h`, `m` and `s` (case insensitive), returning its index in [`UNITS`].
+    ///
+    /// Units must appear in descending order, each at most once: `last` is the index of the
+    /// previously parsed unit.
+    fn unit(&mut self, last: Option<usize>) -> Result<usize> {
+        let expected = "expected unit (`h`, `m` or `s`)";
+        let Some(c) = self.peek() else {
+            return Err(self.error(expected));
+        };
+        let Some(unit) = UNITS
+            .iter()
+            .position(|(unit, _)| *unit == c.to_ascii_lowercase())
+        else {
+            return Err(self.error(expected));
+        };
+
+        if last.is_some_and(|last| unit <= last) {
+            return Err(self.error_at(
+                self.pos,
+                format!("unit `{c}` repeated or out of order (expected `h`, `m`, `s` order)"),
+            ));
+        }
+
+        self.pos += 1;
+        Ok(unit)
+    }
+
+    /// Parse the whole input, returning the number of seconds.
+    fn parse(mut self) -> Result<usize> {
+        self.skip_whitespace();
+
+        let seconds = match self.peek() {
+            None => return Err(self.error("expected a duration")),
+            Some('P' | 'p') => self.iso8601()?,
+            _ if self.chars.contains(&':') => self.clock()?,
+            _ => self.units()?,
+        };
+
+        self.skip_whitespace();
+        if !self.is_end() {
+            return Err(self.error("expected end of duration"));
+        }
+
+        usize::try_from(seconds).map_err(|_| Error::TimerOverflow)
+    }
+
+    /// Parse `[<H>h][<M>m][<S>s]` format, whose components may be separated by whitespace, or
+    /// bare number of minutes.
+    fn units(&mut self) -> Result<u128> {
+        let mut seconds: u128 = 0;
+        let mut last = None;
+
+        while !self.is_end() {
+            let number = self.number()?;
+            self.skip_whitespace();
+
+            // Bare number of minutes.
+            if last.is_none() && self.is_end() {
+                return number.seconds(60);
+            }
+
+            let unit = self.unit(last)?;
+            seconds = seconds
+                .checked_add(number.seconds(UNITS[unit].1)?)
+                .ok_or(Error::TimerOverflow)?;
+            last = Some(unit);
+            self.skip_whitespace();
+        }
+
+        Ok(seconds)
+    }
+
+    /// Parse `M:SS` or `H:MM:SS` format.
+    fn clock(&mut self) -> Result<u128> {
+        let mut fields = vec![self.integer()?];
+        while self.peek() == Some(':') {
+            if fields.len() == 3 {
+                return Err(self.error("expected at most 3 fields (`H:MM:SS`)"));
+            }
+            self.pos += 1;
+
+            let start = self.pos;
+            let field = self.integer()?;
+            if field >= 60 {
+                return Err(self.error_at(start, format!("`{field}` exceeds 59")));
+            }
+            fields.push(field);
+        }
+
+        if fields.len() < 2 {
+            return Err(self.error("expected `:`"));
+        }
+
+        fields.into_iter().try_fold(0u128, |seconds, field| {
+            seconds
+                .checked_mul(60)
+                .and_then(|seconds| seconds.checked_add(field))
+                .ok_or(Error::TimerOverflow)
+        })
+    }
+
+    /// Parse ISO-8601 duration time components (e.g. `PT1H30M`).
+    fn iso8601(&mut self) -> Result<u128> {
+        // Skip `P` designator.
+        self.pos += 1;
+        if !matches!(self.peek(), Some('T' | 't')) {
+            return Err(self.error("expected `T` (only time components are supported)"));
+        }
+        self.pos += 1;
+
+        let mut seconds: u128 = 0;
+        let mut last = None;
+        loop {
+            let number = self.number()?;
+            let unit = self.unit(last)?;
+            seconds = seconds
+                .checked_add(number.seconds(UNITS[unit].1)?)
+                .ok_or(Error::TimerOverflow)?;
+            last = Some(unit);
+
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Ok(seconds);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Parse error message.
+    fn error(s: &str) -> String {
+        parse_duration(s).unwrap_err().to_string()
+    }
+
+    #[test]
+    /// Durations with units.
+    fn duration_units() {
+        assert_eq!(parse_duration("2h10m4s").unwrap(), 7804);
+        assert_eq!(parse_duration("1h 30m").unwrap(), 5400);
+        assert_eq!(parse_duration(" 1 h 30 m ").unwrap(), 5400);
+        assert_eq!(parse_duration("1H30M").unwrap(), 5400);
+        assert_eq!(parse_duration("1.5h").unwrap(), 5400);
+        assert_eq!(parse_duration("0.25m").unwrap(), 15);
+        assert_eq!(parse_duration("90s").unwrap(), 90);
+    }
+
+    #[test]
+    /// Bare number of minutes.
+    fn duration_minutes() {
+        assert_eq!(parse_duration("25").unwrap(), 1500);
+        assert_eq!(parse_duration("1.5").unwrap(), 90);
+    }
+
+    #[test]
+    /// `M:SS` and `H:MM:SS` formats.
+    fn duration_clock() {
+        assert_eq!(parse_duration("25:00").unwrap(), 1500);
+        assert_eq!(parse_duration("1:30:00").unwrap(), 5400);
+        assert_eq!(parse_duration("0:05").unwrap(), 5);
+    }
+
+    #[test]
+    /// ISO-8601 format.
+    fn duration_iso8601() {
+        assert_eq!(parse_duration("PT25M").unwrap(), 1500);
+        assert_eq!(parse_duration("PT1H30M").unwrap(), 5400);
+        assert_eq!(parse_duration("pt1.5h").unwrap(), 5400);
+        assert_eq!(parse_duration("PT10S").unwrap(), 10);
+    }
+
+    #[test]
+    /// Error messages pointing at the offending position.
+    fn duration_errors() {
+        assert_eq!(
+            error(""),
+            "unable to parse duration: unexpected end, expected a duration at position 1 in ``"
+        );
+        assert_eq!(
+            error("1h3x"),
+            "unable to parse duration: unexpected `x`, expected unit (`h`, `m` or `s`) \
+             at position 4 in `1h3x`"
+        );
+        assert_eq!(
+            error("30m 1h"),
+            "unable to parse duration: unit `h` repeated or out of order (expected `h`, `m`, `s` \
+             order) at position 6 in `30m 1h`"
+        );
+        assert_eq!(
+            error("25:75"),
+            "unable to parse duration: `75` exceeds 59 at position 4 in `25:75`"
+        );
+        assert_eq!(
+            error("1:2:3:4"),
+            "unable to parse duration: unexpected `:`, expected at most 3 fields (`H:MM:SS`) \
+             at position 6 in `1:2:3:4`"
+        );
+        assert_eq!(
+            error("P1D"),
+            "unable to parse duration: unexpected `1`, expected `T` (only time components are \
+             supported) at position 2 in `P1D`"
+        );
+        assert_eq!(
+            error("1.h"),
+            "unable to parse duration: unexpected `h`, expected digits after `.` at position 3 \
+             in `1.h`"
+        );
+        assert!(matches!(
+            parse_duration("999999999999999999999999h"),
+            Err(Error::TimerOverflow)
+        ));
+    }
+
+    #[test]
+    /// Time of the day, occurring today or tomorrow.
+    fn clock_time() {
+        let time: ClockTime = "17:30".parse().unwrap();
+        assert_eq!(time, ClockTime(NaiveTime::from_hms_opt(17, 30, 0).unwrap()));
+        assert_eq!(
+            " 7:05:09".parse::<ClockTime>().unwrap(),
+            ClockTime(NaiveTime::from_hms_opt(7, 5, 9).unwrap())
+        );
+        assert_eq!(
+            "25:00".parse::<ClockTime>().unwrap_err().to_string(),
+            "unable to parse clock time: expected `HH:MM` or `HH:MM:SS`, got `25:00`"
+        );
+
+        let now = SystemTime::now();
+        let next = time.next_after(now);
+        assert!(next > now);
+        assert!(next.duration_since(now).unwrap().as_secs() <= 25 * 3600);
+    }
+}
@@ -7,19 +7,21 @@ use crate::figlet;
 pub enum Error {
     /// Occurs when unable to parse [`Timer`](crate::timer::Timer) from string.
     ParseTimer(String),
+    /// Occurs when unable to parse [`ClockTime`](crate::duration::ClockTime) from string.
+    ParseClockTime(String),
     /// Occurs when user tries to setup a [`Timer`](crate::timer::Timer) for a number of seconds
     /// grater than [`usize`](usize::MAX).
     TimerOverflow,
     /// Occurs when unable to initalize [`Terminal`](tui::Terminal) for TUI.
     Terminal(io::Error),
-    /// Occurs when TUI rendering takes more than 1 second, making the timer unreliable.
-    RenderTime,
     /// Occurs when EventHandler hangs up, making the application unresponsive.
     EventHandlerHangUp,
     /// Occurs when given configuration file path does not exist.
     ConfigNotFound(PathBuf),
     /// Occurs on broken configuration.
     Config(toml::de::Error),
+    /// Occurs when the selected configuration profile is missing or broken.
+    Profile(String),
     /// Occurs when unable to send notifications.
     Notification(notify_rust::error::Error),
     /// Occurs when `HOME` environment variable is not set while expanding `~` in path.
@@ -30,6 +32,12 @@ pub enum Error {
     NonUtf8Path(PathBuf),
     /// Occurs provided `.flf` is not a proper FIGlet font file.
     Font(figlet::FontError),
+    /// Occurs when unable to save or load session state.
+    State(PathBuf, String),
+    /// Occurs when unable to read or append to the history file.
+    History(PathBuf, String),
+    /// Occurs when unable to serve or reach the control socket.
+    Socket(PathBuf, String),
     /// Generic error.
     Other(String),
 }
@@ -38,14 +46,15 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::ParseTimer(err) => write!(f, "unable to parse duration: {}", err),
+            Self::ParseClockTime(err) => write!(f, "unable to parse clock time: {}", err),
             Self::TimerOverflow => write!(f, "exceeded maximum timer duration ({}s)", usize::MAX),
             Self::Terminal(err) => write!(f, "terminal error: {}", err),
-            Self::RenderTime => write!(f, "TUI rendering takes too long"),
             Self::EventHandlerHangUp => write!(f, "event handler has hang up unexpectedly"),
             Self::ConfigNotFound(path) => {
                 write!(f, "configuration file not found at `{}`", path.display())
             }
             Self::Config(err) => write!(f, "broken configuration: {}", err),
+            Self::Profile(err) => write!(f, "invalid profile: {}", err),
             Self::Notification(err) => write!(f, "issue on sending desktop notification: {}", err),
             Self::HomeNotFound => write!(
                 f,
@@ -60,6 +69,15 @@ impl fmt::Display for Error {
                 write!(f, "`{}` contains non-UTF8 characters", path.display())
             }
             Self::Font(err) => write!(f, "invalid FIGlet font file: {}", err),
+            Self::State(path, err) => {
+                write!(f, "session state at `{}`: {}", path.display(), err)
+            }
+            Self::History(path, err) => {
+                write!(f, "history at `{}`: {}", path.display(), err)
+            }
+            Self::Socket(path, err) => {
+                write!(f, "control socket at `{}`: {}", path.display(), err)
+            }
             Self::Other(err) => write!(f, "{}", err),
         }
     }
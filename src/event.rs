@@ -1,51 +1,179 @@
-use crate::{ui::UiCommand, Result};
-use crossterm::event::{self, read, KeyCode::Char, KeyEventKind, KeyEventState, KeyModifiers};
+use crate::{session::Interruption, ui::UiCommand, Result};
+use crossterm::event::{
+    self, read,
+    KeyCode::{Backspace, Char, Enter, Esc},
+    KeyEventKind, KeyEventState, KeyModifiers,
+};
+use serde::{Deserialize, Serialize};
 use std::{
-    sync::mpsc::Sender,
+    sync::mpsc::{Receiver, Sender, TryRecvError},
     thread::{self, JoinHandle},
+    time::Duration,
 };
 
+/// Interval between checks for the session to be over, while waiting for terminal events.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// List of application events.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Event {
     TogglePause,
+    /// Pause the running timer, if not paused yet.
+    Pause,
+    /// Resume the paused timer, if paused.
+    Resume,
     Skip,
+    /// Extend the running timer.
+    Extend,
+    /// Shorten the running timer.
+    Shorten,
+    /// Start the next activity, when waiting for confirmation.
+    Start,
+    /// Restart the running timer from its full duration.
+    Restart,
+    /// Abandon the running timer, not counting it.
+    Void,
+    /// Go back to the previous activity.
+    Back,
+    /// Set the task being worked on (or clear it).
+    SetTask(Option<String>),
+    /// Record an interruption of the running pomodoro.
+    Interruption(Interruption),
+    /// Close application.
+    Quit,
+    /// Detach from the session, leaving it running.
+    Detach,
 }
 
 pub struct EventHandler();
 
 impl EventHandler {
     /// Spawn event handler on new trhead.
+    ///
+    /// The event handler returns once the user quits (only detaching, if `detachable`), or once
+    /// `rx_shutdown` is disconnected (i.e. the session ended on its own).
     pub fn spawn_thread(
         tx_event: Sender<Event>,
         tx_ui: Sender<UiCommand>,
+        rx_shutdown: Receiver<()>,
+        detachable: bool,
     ) -> JoinHandle<Result<()>> {
-        thread::spawn(move || -> Result<()> {
-            loop {
-                match read()? {
-                    event::Event::Key(key_event) => {
-                        // Ignore keyboad events which are not press or are not simple key press.
-                        if key_event.modifiers != KeyModifiers::NONE
-                            || key_event.state != KeyEventState::NONE
-                            || key_event.kind != KeyEventKind::Press
-                        {
-                            continue;
-                        }
+        thread::spawn(move || {
+            let tx_quit = tx_event.clone();
+            let result = Self::handle_events(tx_event, tx_ui, rx_shutdown, detachable);
+            // With no keyboard input left, the session (or the attached client) ends all the same,
+            // since other senders (e.g. the control socket) keep `tx_event` connected.
+            if result.is_err() {
+                let _ = tx_quit.send(if detachable {
+                    Event::Detach
+                } else {
+                    Event::Quit
+                });
+            }
+            result
+        })
+    }
+
+    /// Send events for terminal events until the user quits or `rx_shutdown` is disconnected.
+    fn handle_events(
+        tx_event: Sender<Event>,
+        tx_ui: Sender<UiCommand>,
+        rx_shutdown: Receiver<()>,
+        detachable: bool,
+    ) -> Result<()> {
+        // Task being typed by the user, if any.
+        let mut input: Option<String> = None;
+
+        loop {
+            if !event::poll(POLL_INTERVAL)? {
+                match rx_shutdown.try_recv() {
+                    Err(TryRecvError::Empty) => continue,
+                    _ => return Ok(()),
+                }
+            }
 
+            match read()? {
+                event::Event::Key(key_event) => {
+                    // Ignore keyboad events which are not press or are not simple key press
+                    // (`Shift` is allowed, since it's needed to type some symbols).
+                    if !key_event
+                        .modifiers
+                        .difference(KeyModifiers::SHIFT)
+                        .is_empty()
+                        || key_event.state != KeyEventState::NONE
+                        || key_event.kind != KeyEventKind::Press
+                    {
+                        continue;
+                    }
+
+                    // Typing task: keys are text, not commands.
+                    if let Some(task) = input.as_mut() {
                         match key_event.code {
-                            // Pause timer.
-                            Char('p') | Char(' ') => tx_event.send(Event::TogglePause).unwrap(),
-                            // Skip current timer.
-                            Char('s') => tx_event.send(Event::Skip).unwrap(),
-                            // Quit application.
-                            Char('q') => return Ok(()),
+                            Char(c) => task.push(c),
+                            Backspace => {
+                                task.pop();
+                            }
+                            Enter => {
+                                let task = input.take().unwrap();
+                                let task = task.trim();
+                                tx_event
+                                    .send(Event::SetTask(
+                                        (!task.is_empty()).then(|| task.to_string()),
+                                    ))
+                                    .unwrap();
+                            }
+                            Esc => input = None,
                             _ => continue,
                         }
+                        tx_ui.send(UiCommand::Input(input.clone())).unwrap();
+                        continue;
+                    }
+
+                    match key_event.code {
+                        // Set current task.
+                        Char('t') => {
+                            input = Some(String::new());
+                            tx_ui.send(UiCommand::Input(input.clone())).unwrap();
+                        }
+                        // Pause timer.
+                        Char('p') | Char(' ') => tx_event.send(Event::TogglePause).unwrap(),
+                        // Skip current timer.
+                        Char('s') => tx_event.send(Event::Skip).unwrap(),
+                        // Restart/void current timer.
+                        Char('r') => tx_event.send(Event::Restart).unwrap(),
+                        Char('v') => tx_event.send(Event::Void).unwrap(),
+                        // Go back to previous activity.
+                        Char('b') => tx_event.send(Event::Back).unwrap(),
+                        // Extend/shorten current timer.
+                        Char('+') => tx_event.send(Event::Extend).unwrap(),
+                        Char('-') => tx_event.send(Event::Shorten).unwrap(),
+                        // Record internal/external interruption, as tallied (`-` is taken by
+                        // shortening, so external interruptions are tallied with `~`).
+                        Char('\'') => tx_event
+                            .send(Event::Interruption(Interruption::Internal))
+                            .unwrap(),
+                        Char('~') => tx_event
+                            .send(Event::Interruption(Interruption::External))
+                            .unwrap(),
+                        // Start next activity.
+                        Enter => tx_event.send(Event::Start).unwrap(),
+                        // Detach from session hosted by another process, leaving it running:
+                        // quitting it takes an explicit `solanum ctl quit`.
+                        Char('q') | Char('d') if detachable => {
+                            tx_event.send(Event::Detach).unwrap();
+                            return Ok(());
+                        }
+                        // Quit application.
+                        Char('q') => {
+                            tx_event.send(Event::Quit).unwrap();
+                            return Ok(());
+                        }
+                        _ => continue,
                     }
-                    event::Event::Resize(_, _) => tx_ui.send(UiCommand::Refresh).unwrap(),
-                    _ => {} // Ignoring other event types.
                 }
+                event::Event::Resize(_, _) => tx_ui.send(UiCommand::Refresh).unwrap(),
+                _ => {} // Ignoring other event types.
             }
-        })
+        }
     }
 }
@@ -0,0 +1,206 @@
+use crate::{
+    args::{ExportArgs, ExportFormat},
+    error::Error,
+    history::{HistoryEntry, HistoryFile},
+    Result,
+};
+use chrono::{DateTime, Utc};
+use std::{fmt::Write, fs};
+
+/// Quote CSV `field` if needed.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Export `entries` as CSV, with a header line.
+fn to_csv(entries: &[HistoryEntry]) -> String {
+    let mut csv = "start,end,activity,outcome,planned,actual,paused,overtime,task,\
+                   internal_interruptions,external_interruptions\n"
+        .to_string();
+    for entry in entries {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            entry.start.to_rfc3339(),
+            entry.end.to_rfc3339(),
+            entry.activity.kind_name(),
+            entry.outcome.name(),
+            entry.planned,
+            entry.actual,
+            entry.paused,
+            entry.overtime,
+            csv_field(entry.task.as_deref().unwrap_or_default()),
+            entry.interruptions.internal,
+            entry.interruptions.external,
+        )
+        .unwrap();
+    }
+
+    csv
+}
+
+/// Escape iCalendar `text` value.
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold iCalendar content `line` into lines of 75 octets at most, continued with a space.
+fn ics_fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+    for char in line.chars() {
+        if len + char.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(char);
+        len += char.len_utf8();
+    }
+
+    folded
+}
+
+/// Format iCalendar UTC date-time.
+fn ics_time(time: impl Into<DateTime<Utc>>) -> String {
+    time.into().format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Export `entries` as iCalendar, one event per activity with the task as summary.
+fn to_ics(entries: &[HistoryEntry]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//solanum//solanum//EN".to_string(),
+    ];
+    for entry in entries {
+        let summary = match &entry.task {
+            Some(task) => format!("{task} ({})", entry.activity),
+            None => entry.activity.to_string(),
+        };
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            // Activities voided right away may start within the same second as the next one.
+            format!(
+                "UID:{}-{}-{}-{}@solanum",
+                entry.start.timestamp_millis(),
+                entry.end.timestamp_millis(),
+                entry.activity.kind_name(),
+                entry.outcome.name()
+            ),
+            format!("DTSTAMP:{}", ics_time(entry.end)),
+            format!("DTSTART:{}", ics_time(entry.start)),
+            format!("DTEND:{}", ics_time(entry.end)),
+            format!("SUMMARY:{}", ics_text(&summary)),
+            format!("CATEGORIES:{}", entry.activity.kind_name().to_uppercase()),
+            format!("DESCRIPTION:{}", ics_text(entry.outcome.name())),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    // Lines are terminated by CRLF.
+    let lines: Vec<_> = lines.iter().map(|line| ics_fold(line)).collect();
+    lines.join("\r\n") + "\r\n"
+}
+
+/// Export the activity history according to `args`.
+pub fn export(args: &ExportArgs) -> Result<()> {
+    let history_file = HistoryFile::new().ok_or("unable to locate data directory")?;
+    let entries: Vec<_> = history_file
+        .load()?
+        .into_iter()
+        .filter(|entry| args.range.contains(entry.start.date_naive()))
+        .collect();
+
+    let content = match args.format {
+        ExportFormat::Csv => to_csv(&entries),
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(&entries).map_err(|err| Error::Other(err.to_string()))?
+                + "\n"
+        }
+        ExportFormat::Ics => to_ics(&entries),
+    };
+
+    match &args.output {
+        Some(path) => fs::write(path, content)
+            .map_err(|err| Error::Other(format!("unable to write `{}`: {err}", path.display()))),
+        None => {
+            print!("{content}");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        session::{Activity, ActivityRecord},
+        timer::TimerOutcome,
+    };
+    use std::time::{Duration, SystemTime};
+
+    /// Pomodoro on `task` started at 2022-11-14T22:00:00Z.
+    fn entry(task: &str) -> HistoryEntry {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_668_463_200);
+        let duration = Duration::from_secs(25 * 60);
+        let record = ActivityRecord {
+            actual: duration,
+            task: Some(task.to_string()),
+            ..ActivityRecord::new(Activity::Pomodoro(1), duration)
+        };
+        HistoryEntry::new(&record, start, start + duration)
+    }
+
+    #[test]
+    /// History exported as CSV, quoting fields if needed.
+    fn export_csv() {
+        let csv = to_csv(&[entry("Write \"report\", then mail")]);
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("start,end,activity,outcome,"));
+        assert!(lines[1].ends_with(
+            ",pomodoro,completed,1500,1500,0,0,\"Write \"\"report\"\", then mail\",0,0"
+        ));
+    }
+
+    #[test]
+    /// History exported as iCalendar, escaping text.
+    fn export_ics() {
+        let ics = to_ics(&[entry("Write report, then mail")]);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("\r\nDTSTART:20221114T220000Z\r\nDTEND:20221114T222500Z\r\n"));
+        assert!(ics.contains("\r\nSUMMARY:Write report\\, then mail (Pomodoro #1)\r\n"));
+
+        // Unique UIDs, even for activities started within the same second.
+        let voided = HistoryEntry {
+            outcome: TimerOutcome::Voided,
+            end: entry("").start,
+            ..entry("")
+        };
+        let ics = to_ics(&[voided, entry("")]);
+        let uids: Vec<_> = ics
+            .lines()
+            .filter(|line| line.starts_with("UID:"))
+            .collect();
+        assert_eq!(uids.len(), 2);
+        assert_ne!(uids[0], uids[1]);
+
+        // Long lines folded, never splitting characters.
+        let ics = to_ics(&[entry(&"é".repeat(60))]);
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        assert!(ics.contains(&format!(
+            "\r\nSUMMARY:{}\r\n {}",
+            "é".repeat(33),
+            "é".repeat(27)
+        )));
+    }
+}
@@ -121,6 +121,15 @@ const EXCLAMATION: [&str; 5] = [
     r#"(_)"#,
 ];
 
+#[rustfmt::skip]
+const PLUS: [&str; 5] = [
+    r#"       "#,
+    r#"   _   "#,
+    r#" _| |_ "#,
+    r#"|_   _|"#,
+    r#"  |_|  "#,
+];
+
 #[derive(Debug, Clone)]
 /// FIGlet font character.
 struct Char(Vec<String>);
@@ -155,6 +164,7 @@ pub struct Font {
     // Symbols
     dots: Char,
     exclamation: Char,
+    plus: Char,
 }
 
 impl Default for Font {
@@ -178,6 +188,7 @@ impl Default for Font {
             // Symbols
             dots: DOTS.into(),
             exclamation: EXCLAMATION.into(),
+            plus: PLUS.into(),
         }
     }
 }
@@ -283,6 +294,7 @@ impl Font {
 
             dots: map.remove(&':').unwrap(),
             exclamation: map.remove(&'!').unwrap(),
+            plus: map.remove(&'+').unwrap(),
         })
     }
 
@@ -310,6 +322,7 @@ impl Font {
                 // Symbols.
                 ':' => &self.dots,
                 '!' => &self.exclamation,
+                '+' => &self.plus,
                 _ => panic!("unsupported figlet character"),
             }
             .0;
@@ -0,0 +1,196 @@
+use crate::{
+    error::Error,
+    session::{Activity, ActivityRecord, Interruptions},
+    timer::TimerOutcome,
+    Result,
+};
+use chrono::{DateTime, Local};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    time::SystemTime,
+};
+
+/// Entry of the history log, describing a finished activity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Time the activity started.
+    pub start: DateTime<Local>,
+    /// Time the activity ended.
+    pub end: DateTime<Local>,
+    /// Kind of activity.
+    pub activity: Activity,
+    /// How the activity ended.
+    pub outcome: TimerOutcome,
+    /// Planned duration, in seconds.
+    pub planned: u64,
+    /// Time actually spent on the activity, pauses and overtime excluded, in seconds.
+    pub actual: u64,
+    /// Time spent paused, in seconds.
+    pub paused: u64,
+    /// Time spent after the timer expired, in seconds.
+    #[serde(default)]
+    pub overtime: u64,
+    /// Task worked on.
+    #[serde(default)]
+    pub task: Option<String>,
+    /// Interruptions counted while running.
+    #[serde(default)]
+    pub interruptions: Interruptions,
+    /// Whether this entry reverses the latest one of the same run, undone by going back to it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub undone: bool,
+}
+
+impl HistoryEntry {
+    /// Construct [`HistoryEntry`] out of `record` of an activity run from `start` to `end`.
+    pub fn new(record: &ActivityRecord, start: SystemTime, end: SystemTime) -> Self {
+        Self {
+            start: start.into(),
+            end: end.into(),
+            activity: record.activity,
+            outcome: record.outcome,
+            planned: record.planned.as_secs(),
+            actual: record.actual.as_secs(),
+            paused: record.paused.as_secs(),
+            overtime: record.overtime.as_secs(),
+            task: record.task.clone(),
+            interruptions: record.interruptions,
+            undone: false,
+        }
+    }
+}
+
+/// History log of finished activities, in JSON Lines format.
+#[derive(Debug, Clone)]
+pub struct HistoryFile {
+    /// File path.
+    path: PathBuf,
+}
+
+impl HistoryFile {
+    /// Construct [`HistoryFile`] in the XDG data directory, if any.
+    pub fn new() -> Option<Self> {
+        dirs::data_dir().map(|dir| Self::with_path(dir.join("solanum/history.jsonl")))
+    }
+
+    /// Construct [`HistoryFile`] at `path`.
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Construct [`Error::History`] for this file.
+    fn error(&self, err: impl ToString) -> Error {
+        Error::History(self.path.clone(), err.to_string())
+    }
+
+    /// Append `entry` to the log.
+    ///
+    /// The file is locked while writing, and each entry is written at once in append mode, so
+    /// that entries of concurrent instances never interleave.
+    pub fn append(&self, entry: &HistoryEntry) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|err| self.error(err))?;
+        }
+        let mut line = serde_json::to_string(entry).map_err(|err| self.error(err))?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|err| self.error(err))?;
+        file.lock_exclusive().map_err(|err| self.error(err))?;
+        let written = file.write_all(line.as_bytes());
+        // Lock is released on close anyway.
+        let _ = file.unlock();
+
+        written.map_err(|err| self.error(err))
+    }
+
+    /// Load all entries of the log, skipping broken lines (e.g. left by a crash).
+    ///
+    /// Runs undone are left out, along with the entries reversing them.
+    pub fn load(&self) -> Result<Vec<HistoryEntry>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => {
+                let mut entries: Vec<HistoryEntry> = vec![];
+                for entry in content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<HistoryEntry>(line).ok())
+                {
+                    if !entry.undone {
+                        entries.push(entry);
+                    } else if let Some(undone) = entries.iter().rposition(|logged| {
+                        logged.start == entry.start && logged.activity == entry.activity
+                    }) {
+                        entries.remove(undone);
+                    }
+                }
+
+                Ok(entries)
+            }
+            Err(_) if !self.path.exists() => Ok(vec![]),
+            Err(err) => Err(self.error(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    /// Entries appended as JSON lines and loaded back.
+    fn history_append_load() {
+        let dir = std::env::temp_dir().join(format!("solanum-history-{}", std::process::id()));
+        let file = HistoryFile::with_path(dir.join("history.jsonl"));
+
+        let record = ActivityRecord {
+            actual: Duration::from_secs(25 * 60),
+            task: Some("Write report".to_string()),
+            ..ActivityRecord::new(Activity::Pomodoro(1), Duration::from_secs(25 * 60))
+        };
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let end = start + Duration::from_secs(25 * 60);
+        let entry = HistoryEntry::new(&record, start, end);
+        file.append(&entry).unwrap();
+        let skipped = HistoryEntry {
+            activity: Activity::ShortBreak,
+            outcome: TimerOutcome::Skipped,
+            ..entry.clone()
+        };
+        file.append(&skipped).unwrap();
+
+        let content = fs::read_to_string(&file.path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        // Activity kinds spelled as in configuration.
+        assert!(content.contains(r#""activity":{"kind":"short_break"}"#));
+        assert_eq!(file.load().unwrap(), vec![entry.clone(), skipped.clone()]);
+
+        // Broken lines are skipped.
+        fs::write(&file.path, content + "{\"start\":\n").unwrap();
+        file.append(&entry).unwrap();
+        assert_eq!(
+            file.load().unwrap(),
+            vec![entry.clone(), skipped.clone(), entry.clone()]
+        );
+
+        // Undone runs are left out.
+        file.append(&HistoryEntry {
+            undone: true,
+            ..skipped.clone()
+        })
+        .unwrap();
+        assert!(fs::read_to_string(&file.path)
+            .unwrap()
+            .ends_with("\"undone\":true}\n"));
+        assert_eq!(file.load().unwrap(), vec![entry.clone(), entry]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
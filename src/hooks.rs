@@ -0,0 +1,265 @@
+use crate::{timer::Timer, ui::UiCommand};
+use serde::Deserialize;
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+    sync::mpsc::Sender,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Interval between checks for a hook command to be over.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Session events triggering hooks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    PomodoroStart,
+    PomodoroEnd,
+    BreakStart,
+    BreakEnd,
+    Pause,
+    Resume,
+    Skip,
+    SessionEnd,
+}
+
+impl Hook {
+    /// Name of the hook, as in configuration.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PomodoroStart => "on_pomodoro_start",
+            Self::PomodoroEnd => "on_pomodoro_end",
+            Self::BreakStart => "on_break_start",
+            Self::BreakEnd => "on_break_end",
+            Self::Pause => "on_pause",
+            Self::Resume => "on_resume",
+            Self::Skip => "on_skip",
+            Self::SessionEnd => "on_session_end",
+        }
+    }
+}
+
+/// Shell commands run on session events, e.g.:
+///
+/// ```toml
+/// [session.hooks]
+/// on_pomodoro_start = "makoctl mode -a do-not-disturb"
+/// on_pomodoro_end = "makoctl mode -r do-not-disturb"
+/// ```
+///
+/// Commands get the context through `SOLANUM_*` environment variables (see [`HookEnv`]).
+#[derive(Debug, Deserialize, Clone)]
+pub struct Hooks {
+    #[serde(default)]
+    on_pomodoro_start: Option<String>,
+    #[serde(default)]
+    on_pomodoro_end: Option<String>,
+    #[serde(default)]
+    on_break_start: Option<String>,
+    #[serde(default)]
+    on_break_end: Option<String>,
+    #[serde(default)]
+    on_pause: Option<String>,
+    #[serde(default)]
+    on_resume: Option<String>,
+    #[serde(default)]
+    on_skip: Option<String>,
+    #[serde(default)]
+    on_session_end: Option<String>,
+    /// Time after which hook commands are killed.
+    #[serde(default = "default_timeout")]
+    timeout: Timer,
+}
+
+#[inline]
+fn default_timeout() -> Timer {
+    Timer::new(0, 0, 10)
+}
+
+impl Default for Hooks {
+    fn default() -> Self {
+        Self {
+            on_pomodoro_start: None,
+            on_pomodoro_end: None,
+            on_break_start: None,
+            on_break_end: None,
+            on_pause: None,
+            on_resume: None,
+            on_skip: None,
+            on_session_end: None,
+            timeout: default_timeout(),
+        }
+    }
+}
+
+/// Environment variables passed to hook commands.
+#[derive(Debug, Clone, Default)]
+pub struct HookEnv(Vec<(&'static str, String)>);
+
+impl HookEnv {
+    /// Set variable `SOLANUM_<name>` to `value`.
+    pub fn set(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.0.retain(|(set, _)| *set != name);
+        self.0.push((name, value.to_string()));
+        self
+    }
+}
+
+impl Hooks {
+    /// Command configured for `hook`, if any.
+    fn command(&self, hook: Hook) -> Option<&str> {
+        match hook {
+            Hook::PomodoroStart => &self.on_pomodoro_start,
+            Hook::PomodoroEnd => &self.on_pomodoro_end,
+            Hook::BreakStart => &self.on_break_start,
+            Hook::BreakEnd => &self.on_break_end,
+            Hook::Pause => &self.on_pause,
+            Hook::Resume => &self.on_resume,
+            Hook::Skip => &self.on_skip,
+            Hook::SessionEnd => &self.on_session_end,
+        }
+        .as_deref()
+    }
+
+    /// Run command configured for `hook` in the background, with `env` variables.
+    ///
+    /// Failures (and commands killed after `timeout`) are reported to the Ui as notices, never
+    /// affecting the timer.
+    pub fn run(
+        &self,
+        hook: Hook,
+        env: &HookEnv,
+        tx_ui: &Sender<UiCommand>,
+    ) -> Option<JoinHandle<()>> {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(self.command(hook)?)
+            .env("SOLANUM_HOOK", hook.name())
+            .envs(
+                env.0
+                    .iter()
+                    .map(|(name, value)| (format!("SOLANUM_{name}"), value)),
+            )
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        let timeout = self.timeout;
+        let tx_ui = tx_ui.clone();
+
+        Some(thread::spawn(move || {
+            if let Err(err) = wait(command, timeout) {
+                // Ui may be gone already, once the session is over.
+                let _ = tx_ui.send(UiCommand::Notice(format!(
+                    "Hook `{}` failed: {err}",
+                    hook.name()
+                )));
+            }
+        }))
+    }
+}
+
+/// Run `command`, waiting for it for `timeout` at most.
+fn wait(mut command: Command, timeout: Timer) -> Result<(), String> {
+    let mut child = command.spawn().map_err(|err| err.to_string())?;
+    // Error output is drained meanwhile, so that a verbose command never blocks on a full pipe.
+    let stderr = child.stderr.take().map(|mut pipe| {
+        thread::spawn(move || {
+            let mut stderr = String::new();
+            let _ = pipe.read_to_string(&mut stderr);
+            stderr
+        })
+    });
+    let deadline = Instant::now() + timeout.duration();
+    let status = loop {
+        match child.try_wait().map_err(|err| err.to_string())? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("killed after {timeout}"));
+            }
+            None => thread::sleep(POLL_INTERVAL),
+        }
+    };
+    if status.success() {
+        return Ok(());
+    }
+
+    // First line of the error output tells what went wrong.
+    let stderr = stderr
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+    match stderr.lines().next() {
+        Some(line) => Err(format!("{status}: {line}")),
+        None => Err(status.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{fs, sync::mpsc};
+
+    #[test]
+    /// Hook commands run with context variables, failures reported as notices.
+    fn hooks_run() {
+        let file = std::env::temp_dir().join(format!("solanum-hook-{}", std::process::id()));
+        let hooks = Hooks {
+            on_pomodoro_start: Some(format!(
+                "echo \"$SOLANUM_HOOK $SOLANUM_ACTIVITY $SOLANUM_TASK\" > {}",
+                file.display()
+            )),
+            on_pause: Some("echo 'no such thing' >&2; exit 3".to_string()),
+            on_resume: Some("sleep 5".to_string()),
+            on_skip: Some("head -c 200000 /dev/zero >&2".to_string()),
+            timeout: Timer::new(0, 0, 1),
+            ..Default::default()
+        };
+        let env = HookEnv::default()
+            .set("ACTIVITY", "pomodoro")
+            .set("TASK", "Write report");
+        let (tx_ui, rx_ui) = mpsc::channel();
+
+        hooks
+            .run(Hook::PomodoroStart, &env, &tx_ui)
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "on_pomodoro_start pomodoro Write report\n"
+        );
+        fs::remove_file(file).unwrap();
+        assert!(hooks.run(Hook::BreakEnd, &env, &tx_ui).is_none());
+        // Output larger than the pipe buffer doesn't block the command.
+        hooks.run(Hook::Skip, &env, &tx_ui).unwrap().join().unwrap();
+
+        hooks
+            .run(Hook::Pause, &env, &tx_ui)
+            .unwrap()
+            .join()
+            .unwrap();
+        hooks
+            .run(Hook::Resume, &env, &tx_ui)
+            .unwrap()
+            .join()
+            .unwrap();
+        drop(tx_ui);
+        let notices: Vec<_> = rx_ui
+            .iter()
+            .map(|ui_command| match ui_command {
+                UiCommand::Notice(notice) => notice,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            notices,
+            [
+                "Hook `on_pause` failed: exit status: 3: no such thing",
+                "Hook `on_resume` failed: killed after 1s",
+            ]
+        );
+    }
+}
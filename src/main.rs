@@ -1,47 +1,149 @@
 mod args;
+mod clock;
 mod config;
+mod control;
+mod duration;
 mod error;
 mod event;
+mod export;
 mod figlet;
+mod history;
+mod hooks;
 mod notification;
 mod path;
 mod session;
+mod state;
+mod stats;
+mod status;
 mod timer;
 mod ui;
 
-use args::Args;
+use args::{Args, Command};
+use clock::SystemClock;
 use config::Config;
+use control::{Hub, Server};
 use error::Error;
 use event::EventHandler;
-use std::{process::ExitCode, sync::mpsc};
+use history::HistoryFile;
+use state::StateFile;
+use std::{
+    process::ExitCode,
+    sync::{mpsc, Arc},
+    time::SystemTime,
+};
+use ui::UiCommand;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 fn run() -> Result<()> {
     // Parse CLI arguments.
     let args = Args::new();
+    // Subcommands don't run the timer.
+    if let Some(command) = args.get_command() {
+        return match command {
+            Command::Stats(stats_args) => stats::print(stats_args),
+            Command::Export(export_args) => export::export(export_args),
+            Command::Ctl(ctl_args) => control::ctl(ctl_args.command),
+            Command::Status(status_args) => status::print(status_args),
+            Command::Attach => {
+                let path = control::socket_path().ok_or("unable to locate runtime directory")?;
+                let (_, ui) = Config::new(args.get_config_path(), args.get_profile())?.split();
+                control::attach(&path, ui)
+            }
+        };
+    }
+    let resume = args.get_resume();
+    let headless = args.get_headless();
     // Parse configuration and override with CLI arguments.
-    let config = Config::new(args.get_config_path())?.override_with_args(args)?;
+    let config =
+        Config::new(args.get_config_path(), args.get_profile())?.override_with_args(args)?;
 
     // Retrieve `Session` and `Ui` from configuration.
     let (mut session, ui) = config.split();
 
+    // Resume previous session, if asked to, and keep its state saved from now on. Starting over
+    // instead is worth a notice.
+    let state_file = StateFile::new();
+    let resume_notice = match (resume, &state_file) {
+        (false, _) => None,
+        (true, None) => Some("Unable to locate state directory: starting over".to_string()),
+        (true, Some(state_file)) => match state_file.load() {
+            Ok(Some(state)) if session.resume(&state, SystemTime::now()) => None,
+            Ok(Some(_)) => Some(
+                "Previous session too old or not matching the sequence: starting over".to_string(),
+            ),
+            Ok(None) => Some("No previous session to resume: starting over".to_string()),
+            Err(err) => Some(format!("Unable to resume {err}: starting over")),
+        },
+    };
+    session.state_file = state_file;
+    // Log finished activities.
+    session.history_file = HistoryFile::new();
+
     // Channel to send data from logic thread (`session`) to UI thread (`ui`).
     let (tx_ui, rx_ui) = mpsc::channel();
     // Channel to send events from EventHandler to logic thread (`session`).
     let (tx_event, rx_event) = mpsc::channel();
+    // Channel to shut EventHandler down, once disconnected.
+    let (tx_shutdown, rx_shutdown) = mpsc::channel();
+    // Channel to send data from logic thread to relay thread, tracking the session status.
+    let (tx_relay, rx_relay) = mpsc::channel();
+    let hub = Arc::new(Hub::default());
+
+    if let Some(notice) = resume_notice {
+        tx_ui.send(UiCommand::Notice(notice)).unwrap();
+    }
 
-    // Spawn event handler to handle keyboard events and terminal resize.
-    let event_handler_thread = EventHandler::spawn_thread(tx_event, tx_ui.clone());
+    // Listen for commands on the control socket, if any, while the session runs.
+    let server =
+        control::socket_path().map(|path| Server::bind(path, tx_event.clone(), Arc::clone(&hub)));
+    let _server = match server {
+        Some(Ok(server)) => Some(server),
+        // With no terminal, the session could never be controlled.
+        Some(Err(err)) if headless => return Err(err),
+        None if headless => {
+            return Err("unable to locate runtime directory for control socket".into())
+        }
+        // The terminal is enough to drive the session (e.g. another instance owns the socket).
+        Some(Err(err)) => {
+            tx_ui
+                .send(UiCommand::Notice(format!("No remote control: {err}")))
+                .unwrap();
+            None
+        }
+        None => None,
+    };
 
-    // Spawn Ui thread.
-    let renderer_thread = ui.spawn_thread(rx_ui)?;
-    // Session logic (timers).
-    session.start(tx_ui, rx_event)?;
+    // With no terminal, the session is driven through the control socket only: `tx_event` is
+    // kept until the session ends, and Ui commands are just logged.
+    let (event_handler_thread, renderer_thread, _tx_event) = if headless {
+        (None, ui::spawn_log_thread(rx_ui), Some(tx_event))
+    } else {
+        // Spawn event handler to handle keyboard events and terminal resize.
+        let event_handler_thread =
+            EventHandler::spawn_thread(tx_event, tx_ui.clone(), rx_shutdown, false);
+        (Some(event_handler_thread), ui.spawn_thread(rx_ui)?, None)
+    };
+
+    // Spawn relay thread.
+    let relay_thread = control::spawn_relay(rx_relay, tx_ui, hub);
+    // Session logic (timers), whose error is returned only once the terminal is restored.
+    let result = session.start(&SystemClock, tx_relay, rx_event);
+    // Session may have ended on its own, while EventHandler is still running.
+    drop(tx_shutdown);
 
     // Join threads.
+    relay_thread.join().unwrap();
     renderer_thread.join().unwrap()?;
-    event_handler_thread.join().unwrap()?;
+    if let Some(event_handler_thread) = event_handler_thread {
+        event_handler_thread.join().unwrap()?;
+    }
+    result?;
+
+    // Print session summary once the terminal is restored.
+    if let Some(summary) = session.summary() {
+        println!("{summary}");
+    }
 
     Ok(())
 }
@@ -1,25 +1,52 @@
-use crate::{session::Activity, Result};
+use crate::{
+    session::{Activity, Goal},
+    timer::{Timer, TimerOutcome},
+    Result,
+};
 use notify_rust::{Notification, NotificationHandle, Urgency};
 
-pub fn notify(activity: Activity) -> Result<NotificationHandle> {
-    let (summary, body, urgency) = match activity {
-        Activity::Pomodoro(num) => (
+/// Send desktop notification for `activity` ended with `outcome`, mentioning `task` if any.
+///
+/// Completed activities are notified prominently, while skipped and voided ones (ended by the
+/// user) only get a low urgency notification.
+pub fn notify(
+    activity: Activity,
+    outcome: TimerOutcome,
+    task: Option<&str>,
+) -> Result<NotificationHandle> {
+    let (summary, body, urgency) = match (outcome, activity) {
+        (TimerOutcome::Skipped, _) => (
+            "Activity skipped",
+            format!("{} skipped", activity),
+            Urgency::Low,
+        ),
+        (TimerOutcome::Voided, _) => (
+            "Activity voided",
+            format!("{} voided: it won't be counted", activity),
+            Urgency::Low,
+        ),
+        (_, Activity::Pomodoro(num)) => (
             "Pomodoro completed",
             format!("Pomodoro #{} completed", num),
             Urgency::Normal,
         ),
-        Activity::ShortBreak => (
+        (_, Activity::ShortBreak) => (
             "Short break ended",
             "Prepare for next pomodoro".to_string(),
             Urgency::Critical,
         ),
-        Activity::LongBreak => (
+        (_, Activity::LongBreak) => (
             "Long break ended",
             "Prepare for next pomodoro".to_string(),
             Urgency::Critical,
         ),
     };
 
+    let body = match task {
+        Some(task) => format!("{body}\nTask: {task}"),
+        None => body,
+    };
+
     Ok(Notification::new()
         .appname("Solanum")
         .summary(summary)
@@ -28,3 +55,26 @@ pub fn notify(activity: Activity) -> Result<NotificationHandle> {
         .timeout(5000)
         .show()?)
 }
+
+/// Send desktop notification reminding that `activity` has been paused for more than
+/// `max_pause`.
+pub fn remind(activity: Activity, max_pause: Timer) -> Result<NotificationHandle> {
+    Ok(Notification::new()
+        .appname("Solanum")
+        .summary("Timer still paused")
+        .body(&format!("{} paused for more than {}", activity, max_pause))
+        .urgency(Urgency::Normal)
+        .timeout(5000)
+        .show()?)
+}
+
+/// Send desktop notification celebrating the daily `goal` reached.
+pub fn celebrate(goal: Goal) -> Result<NotificationHandle> {
+    Ok(Notification::new()
+        .appname("Solanum")
+        .summary("Daily goal reached!")
+        .body(&format!("Well done: {} today", goal))
+        .urgency(Urgency::Normal)
+        .timeout(5000)
+        .show()?)
+}
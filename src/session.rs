@@ -1,18 +1,49 @@
-use crate::{event::Event, timer::Timer, ui::UiCommand, Result};
-use serde::Deserialize;
+use crate::{
+    clock::Clock,
+    duration::ClockTime,
+    event::Event,
+    history::{HistoryEntry, HistoryFile},
+    hooks::{Hook, HookEnv, Hooks},
+    notification::celebrate,
+    state::{SessionState, StateFile},
+    stats::Summary,
+    timer::{Timer, TimerOptions, TimerOutcome, TimerStatus},
+    ui::{Color, UiCommand},
+    Result,
+};
+use chrono::{DateTime, Local};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Serialize,
+};
 use std::{
+    collections::VecDeque,
     fmt::{self, Display},
-    sync::mpsc::{Receiver, Sender},
+    str::FromStr,
+    sync::mpsc::{Receiver, RecvError, Sender},
+    time::{Duration, SystemTime},
 };
 
 /// Kind of activity associated to the timer.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "num")]
 pub enum Activity {
     Pomodoro(u8),
     ShortBreak,
     LongBreak,
 }
 
+impl Activity {
+    /// Name of the kind of activity (e.g. `short_break`).
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Self::Pomodoro(_) => "pomodoro",
+            Self::ShortBreak => "short_break",
+            Self::LongBreak => "long_break",
+        }
+    }
+}
+
 impl Display for Activity {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -23,8 +54,246 @@ impl Display for Activity {
     }
 }
 
+/// Kind of activity of a sequence [`Step`].
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Pomodoro,
+    ShortBreak,
+    LongBreak,
+}
+
+/// Step of the activity sequence.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Step {
+    /// Kind of activity.
+    pub kind: ActivityKind,
+    /// Activity duration.
+    pub duration: Timer,
+    /// Label shown along with the activity.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Color overriding the one of the activity kind.
+    #[serde(default)]
+    pub color: Option<Color>,
+}
+
+impl Step {
+    /// Construct a new [`Step`] with no label nor color.
+    pub fn new(kind: ActivityKind, duration: Timer) -> Self {
+        Self {
+            kind,
+            duration,
+            label: None,
+            color: None,
+        }
+    }
+}
+
+/// Daily goal, counted over the day (earlier runs logged in the history included).
+#[derive(Debug, Clone, Copy)]
+pub enum Goal {
+    /// Number of completed pomodoros (e.g. `daily_goal = 8`).
+    Pomodoros(u8),
+    /// Focus time (e.g. `daily_goal = "4h"`).
+    FocusTime(Timer),
+}
+
+struct GoalVisitor;
+
+impl<'de> Visitor<'de> for GoalVisitor {
+    type Value = Goal;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .write_str("number of pomodoros (e.g. `8`) or focus time with a unit (e.g. `\"4h\"`)")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        u8::try_from(v).map(Goal::Pomodoros).map_err(|_| {
+            E::custom(format!(
+                "goal of {v} pomodoros is out of range (255 at most)"
+            ))
+        })
+    }
+
+    fn visit_i64<E>(self, v: i64) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        u8::try_from(v).map(Goal::Pomodoros).map_err(|_| {
+            E::custom(format!(
+                "goal of {v} pomodoros is out of range (255 at most)"
+            ))
+        })
+    }
+
+    /// Bare numbers would be minutes as durations, easily mistaken for pomodoros.
+    fn visit_str<E>(self, v: &str) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if v.trim().parse::<f64>().is_ok() {
+            return Err(E::custom(format!(
+                "focus time goal `{v}` needs a unit (e.g. `{v}h`)"
+            )));
+        }
+        Timer::from_str(v).map(Goal::FocusTime).map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Goal {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(GoalVisitor)
+    }
+}
+
+impl Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pomodoros(num) => write!(f, "{} pomodoros", num),
+            Self::FocusTime(focus_time) => write!(f, "{} of focus time", focus_time),
+        }
+    }
+}
+
+/// What to do with the running timer when the system has been suspended.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SuspendPolicy {
+    /// Count the time slept toward the running timer.
+    #[default]
+    Count,
+    /// Pause the running timer, not counting the time slept.
+    Pause,
+    /// Abandon the running timer: voided pomodoros are not counted.
+    Void,
+}
+
+/// What to do with the paused timer once paused for longer than `max_pause`.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PausePolicy {
+    /// Send a reminder, keeping the timer paused.
+    #[default]
+    Remind,
+    /// Abandon the paused pomodoro: it's not counted.
+    Void,
+}
+
+/// Kind of interruption of a running pomodoro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interruption {
+    /// Interruption coming from the user (e.g. a sudden urge to check mail).
+    Internal,
+    /// Interruption coming from someone else (e.g. a phone call).
+    External,
+}
+
+/// Interruptions counted during a pomodoro.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interruptions {
+    /// Internal interruptions.
+    pub internal: u8,
+    /// External interruptions.
+    pub external: u8,
+}
+
+impl Interruptions {
+    /// Count one more `interruption`.
+    pub fn add(&mut self, interruption: Interruption) {
+        match interruption {
+            Interruption::Internal => self.internal = self.internal.saturating_add(1),
+            Interruption::External => self.external = self.external.saturating_add(1),
+        }
+    }
+}
+
+impl Display for Interruptions {
+    /// Tally marks, typed with the same keys: `'` per internal interruption, `~` per external
+    /// one.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            "'".repeat(self.internal as usize),
+            "~".repeat(self.external as usize)
+        )
+    }
+}
+
+/// Maximum number of transitions kept to go back to previous activities.
+const HISTORY_LEN: usize = 8;
+
+/// Record of an activity run.
+#[derive(Debug, Clone)]
+pub struct ActivityRecord {
+    /// Kind of activity.
+    pub activity: Activity,
+    /// How the activity ended.
+    pub outcome: TimerOutcome,
+    /// Planned duration.
+    pub planned: Duration,
+    /// Time added (positive) or removed (negative) while running, in seconds.
+    pub adjustment: i64,
+    /// Time actually spent on the activity, pauses and overtime excluded.
+    pub actual: Duration,
+    /// Time spent on the activity after its timer expired.
+    pub overtime: Duration,
+    /// Time spent paused.
+    pub paused: Duration,
+    /// Time left on the timer when the activity ended.
+    pub remaining: Duration,
+    /// Task worked on.
+    pub task: Option<String>,
+    /// Interruptions counted while running (pomodoros only).
+    pub interruptions: Interruptions,
+    /// Whether the user quit once the activity was over (e.g. from the expired screen).
+    pub quit: bool,
+}
+
+impl ActivityRecord {
+    /// Construct a new [`ActivityRecord`] for an activity about to start.
+    pub fn new(activity: Activity, planned: Duration) -> Self {
+        Self {
+            activity,
+            outcome: TimerOutcome::Completed,
+            planned,
+            adjustment: 0,
+            actual: Duration::ZERO,
+            overtime: Duration::ZERO,
+            paused: Duration::ZERO,
+            remaining: Duration::ZERO,
+            task: None,
+            interruptions: Interruptions::default(),
+            quit: false,
+        }
+    }
+}
+
+/// Transition from an activity to the next one, allowing to go back to it.
+#[derive(Debug, Clone)]
+struct Transition {
+    /// Step left.
+    step: usize,
+    /// Time left on its timer.
+    remaining: Duration,
+    /// Count of completed pomodoros before leaving it.
+    pomodoro_count: u8,
+    /// Index of the record of its run in [`Session::records`].
+    record: usize,
+    /// History entry logged for its run, if any.
+    entry: Option<HistoryEntry>,
+}
+
 /// **Solanum** session.
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct Session {
     /// Count of completed pomorodos.
     #[serde(skip)]
@@ -41,6 +310,91 @@ pub struct Session {
     /// Pomodoros before long break.
     #[serde(default = "default_pomodoros")]
     pub pomodoros: u8,
+    /// Sequence of steps, repeated in a loop. If empty, the classic cycle is built out of
+    /// `pomodoro`, `short_break`, `long_break` and `pomodoros`.
+    ///
+    /// Durations given on the command line (`-p`, `-s`, `-l`) override the ones of the steps of
+    /// the matching kind, while `-n` is rejected.
+    #[serde(default)]
+    pub sequence: Vec<Step>,
+    /// Policy applied to the running timer on system suspend.
+    #[serde(default)]
+    pub on_suspend: SuspendPolicy,
+    /// Send desktop notifications when timers expire.
+    #[serde(default = "default_notifications")]
+    pub notifications: bool,
+    /// Time added or removed when extending or shortening the running timer.
+    #[serde(default = "default_adjust_step")]
+    pub adjust_step: Timer,
+    /// Keep counting upward once a pomodoro expires, until moving on explicitly.
+    #[serde(default)]
+    pub overtime: bool,
+    /// Start breaks with no user confirmation.
+    #[serde(default = "default_auto_start")]
+    pub auto_start_breaks: bool,
+    /// Start pomodoros with no user confirmation.
+    #[serde(default = "default_auto_start")]
+    pub auto_start_pomodoros: bool,
+    /// Pause duration after which `on_max_pause` is applied to the paused pomodoro.
+    #[serde(default)]
+    pub max_pause: Option<Timer>,
+    /// Policy applied to pomodoros paused for longer than `max_pause`.
+    #[serde(default)]
+    pub on_max_pause: PausePolicy,
+    /// Daily goal, whose progress is shown while running.
+    #[serde(default)]
+    pub daily_goal: Option<Goal>,
+    /// End the session once `daily_goal` is reached.
+    #[serde(default)]
+    pub stop_at_goal: bool,
+    /// End the session after this many cycles through the sequence.
+    #[serde(default)]
+    pub cycles: Option<u32>,
+    /// End the session after this many completed pomodoros.
+    #[serde(default)]
+    pub max_pomodoros: Option<u32>,
+    /// End the session at this time of the day, once the running activity is over.
+    #[serde(default)]
+    pub until: Option<ClockTime>,
+    /// Age after which the state left by a previous run is too old to be resumed.
+    #[serde(default = "default_resume_cutoff")]
+    pub resume_cutoff: Timer,
+    /// Commands run on session events.
+    #[serde(default)]
+    pub hooks: Hooks,
+    /// Task being worked on.
+    #[serde(skip)]
+    pub task: Option<String>,
+    /// Records of the activities run so far.
+    #[serde(skip)]
+    pub records: Vec<ActivityRecord>,
+    /// Latest transitions, most recent last.
+    #[serde(skip)]
+    history: VecDeque<Transition>,
+    /// Step to start with, if resumed from a previous run.
+    #[serde(skip)]
+    resumed: Option<usize>,
+    /// File where the state of the session is saved while running.
+    #[serde(skip)]
+    pub state_file: Option<StateFile>,
+    /// File where finished activities are logged.
+    #[serde(skip)]
+    pub history_file: Option<HistoryFile>,
+    /// Whether `daily_goal` has been reached.
+    #[serde(skip)]
+    goal_reached: bool,
+    /// Progress toward `daily_goal` made earlier today by previous runs.
+    #[serde(skip)]
+    goal_base: Summary,
+    /// Record of the activity gone back to, carried over to its next run.
+    #[serde(skip)]
+    carried: Option<ActivityRecord>,
+    /// History entry logged for the last run, if any.
+    #[serde(skip)]
+    logged: Option<HistoryEntry>,
+    /// Count of cycles through the sequence completed.
+    #[serde(skip)]
+    cycles_done: u32,
 }
 
 #[inline]
@@ -63,6 +417,26 @@ fn default_pomodoros() -> u8 {
     4
 }
 
+#[inline]
+fn default_notifications() -> bool {
+    true
+}
+
+#[inline]
+fn default_auto_start() -> bool {
+    true
+}
+
+#[inline]
+fn default_adjust_step() -> Timer {
+    Timer::new(0, 5, 0)
+}
+
+#[inline]
+fn default_resume_cutoff() -> Timer {
+    Timer::new(1, 0, 0)
+}
+
 impl Default for Session {
     fn default() -> Self {
         Self {
@@ -71,45 +445,990 @@ impl Default for Session {
             short_break: default_short_break(),
             long_break: default_long_break(),
             pomodoros: default_pomodoros(),
+            sequence: vec![],
+            on_suspend: Default::default(),
+            notifications: default_notifications(),
+            adjust_step: default_adjust_step(),
+            overtime: false,
+            auto_start_breaks: default_auto_start(),
+            auto_start_pomodoros: default_auto_start(),
+            max_pause: None,
+            on_max_pause: Default::default(),
+            daily_goal: None,
+            stop_at_goal: false,
+            cycles: None,
+            max_pomodoros: None,
+            until: None,
+            resume_cutoff: default_resume_cutoff(),
+            task: None,
+            records: vec![],
+            history: VecDeque::new(),
+            resumed: None,
+            state_file: None,
+            history_file: None,
+            hooks: Hooks::default(),
+            goal_base: Summary::default(),
+            carried: None,
+            logged: None,
+            goal_reached: false,
+            cycles_done: 0,
         }
     }
 }
 
 impl Session {
-    /// Start [`Session`].
-    pub fn start(&mut self, tx_ui: Sender<UiCommand>, rx_event: Receiver<Event>) -> Result<()> {
+    /// Fill the sequence with the classic cycle if none has been configured: `pomodoros`
+    /// pomodoros with short breaks in between, then a long break.
+    fn init_sequence(&mut self) {
+        if !self.sequence.is_empty() {
+            return;
+        }
+
+        let pomodoros = self.pomodoros.max(1);
+        for num in 1..=pomodoros {
+            self.sequence
+                .push(Step::new(ActivityKind::Pomodoro, self.pomodoro));
+            self.sequence.push(match num {
+                _ if num == pomodoros => Step::new(ActivityKind::LongBreak, self.long_break),
+                _ => Step::new(ActivityKind::ShortBreak, self.short_break),
+            });
+        }
+    }
+
+    /// Activity of the sequence `step`.
+    fn activity(&self, step: usize) -> Activity {
+        match self.sequence[step].kind {
+            ActivityKind::Pomodoro => Activity::Pomodoro(self.pomodoro_count + 1),
+            ActivityKind::ShortBreak => Activity::ShortBreak,
+            ActivityKind::LongBreak => Activity::LongBreak,
+        }
+    }
+
+    /// [`TimerOptions`] for the timer of `step`.
+    fn timer_options(&self, step: usize) -> TimerOptions {
+        let pomodoro = self.sequence[step].kind == ActivityKind::Pomodoro;
+        TimerOptions {
+            on_suspend: self.on_suspend,
+            notifications: self.notifications,
+            adjust_step: self.adjust_step.duration(),
+            overtime: self.overtime && pomodoro,
+            auto_start_next: self.auto_start(self.next(step)),
+            max_pause: self
+                .max_pause
+                .filter(|_| pomodoro)
+                .map(|max_pause| max_pause.duration()),
+            on_max_pause: self.on_max_pause,
+        }
+    }
+
+    /// Whether `step` starts with no user confirmation.
+    fn auto_start(&self, step: usize) -> bool {
+        match self.sequence[step].kind {
+            ActivityKind::Pomodoro => self.auto_start_pomodoros,
+            ActivityKind::ShortBreak | ActivityKind::LongBreak => self.auto_start_breaks,
+        }
+    }
+
+    /// Step following `step`, looping over the sequence.
+    fn next(&self, step: usize) -> usize {
+        (step + 1) % self.sequence.len()
+    }
+
+    /// Time actually spent on pomodoros, voided ones excluded.
+    pub fn focus_time(&self) -> Duration {
+        self.records
+            .iter()
+            .filter(|record| {
+                matches!(record.activity, Activity::Pomodoro(_))
+                    && record.outcome != TimerOutcome::Voided
+            })
+            .map(|record| record.actual + record.overtime)
+            .sum()
+    }
+
+    /// Number of completed pomodoros (i.e. not skipped nor voided).
+    pub fn completed_pomodoros(&self) -> usize {
+        self.records
+            .iter()
+            .filter(|record| {
+                matches!(record.activity, Activity::Pomodoro(_))
+                    && record.outcome == TimerOutcome::Completed
+            })
+            .count()
+    }
+
+    /// Count progress toward `daily_goal` made earlier on the day of `now`, as logged in the
+    /// history file: a goal already reached is not celebrated again.
+    ///
+    /// That's best effort: failing to load the history is reported to the Ui.
+    fn load_goal_base(&mut self, now: SystemTime, tx_ui: &Sender<UiCommand>) {
+        let (Some(goal), Some(history_file)) = (self.daily_goal, &self.history_file) else {
+            return;
+        };
+
+        match history_file.load() {
+            Ok(entries) => {
+                let today = DateTime::<Local>::from(now).date_naive();
+                self.goal_base = Summary::of_day(&entries, today);
+                self.goal_reached = self.goal_progress(goal).1;
+            }
+            Err(err) => tx_ui
+                .send(UiCommand::Notice(format!("Unable to load {err}")))
+                .unwrap(),
+        }
+    }
+
+    /// Progress toward `goal` to show, and whether it's reached.
+    fn goal_progress(&self, goal: Goal) -> (String, bool) {
+        match goal {
+            Goal::Pomodoros(num) => {
+                let completed = self.goal_base.completed + self.completed_pomodoros();
+                (
+                    format!("Goal: {completed}/{num} pomodoros"),
+                    completed >= num as usize,
+                )
+            }
+            Goal::FocusTime(goal) => {
+                let focus_time = Duration::from_secs(self.goal_base.focus_time) + self.focus_time();
+                (
+                    format!(
+                        "Goal: {}/{} of focus time",
+                        Timer::new(0, 0, focus_time.as_secs() as usize),
+                        goal
+                    ),
+                    focus_time >= goal.duration(),
+                )
+            }
+        }
+    }
+
+    /// Send progress toward `daily_goal` to the Ui, celebrating the goal once reached.
+    ///
+    /// Return whether the goal has just been reached.
+    fn update_goal(&mut self, tx_ui: &Sender<UiCommand>) -> Result<bool> {
+        let Some(goal) = self.daily_goal else {
+            return Ok(false);
+        };
+
+        let (progress, reached) = self.goal_progress(goal);
+        tx_ui.send(UiCommand::Goal(progress)).unwrap();
+
+        if !reached || self.goal_reached {
+            return Ok(false);
+        }
+        self.goal_reached = true;
+        if self.notifications {
+            celebrate(goal)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Time spent paused, over all activities.
+    pub fn pause_time(&self) -> Duration {
+        self.records.iter().map(|record| record.paused).sum()
+    }
+
+    /// Session summary, if any focus time has been recorded.
+    pub fn summary(&self) -> Option<String> {
+        let focus_time = self.focus_time().as_secs() as usize;
+        if focus_time == 0 {
+            return None;
+        }
+
+        let mut summary = format!(
+            "Focus time: {}, {} pomodoros completed",
+            Timer::new(0, 0, focus_time),
+            self.completed_pomodoros()
+        );
+        let pause_time = self.pause_time().as_secs() as usize;
+        if pause_time > 0 {
+            summary.push_str(&format!(", paused for {}", Timer::new(0, 0, pause_time)));
+        }
+
+        Some(summary)
+    }
+
+    /// [`Timer`] of `step`.
+    fn timer(&mut self, step: usize) -> &mut Timer {
+        &mut self.sequence[step].duration
+    }
+
+    /// Remember the transition leaving `step`, forgetting the oldest one if needed.
+    ///
+    /// `record` of the run left must be the last one of `records`.
+    fn push_transition(&mut self, step: usize, record: &ActivityRecord) {
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(Transition {
+            step,
+            remaining: record.remaining,
+            pomodoro_count: self.pomodoro_count,
+            record: self.records.len() - 1,
+            entry: self.logged.take(),
+        });
+    }
+
+    /// Move on from `step` to the next one, keeping track of the transition and of the cycles
+    /// completed.
+    fn advance(&mut self, step: usize, record: &ActivityRecord) -> usize {
+        self.push_transition(step, record);
+        let next = self.next(step);
+        if next == 0 {
+            self.cycles_done += 1;
+        }
+
+        next
+    }
+
+    /// Whether one of the session bounds (`cycles`, `max_pomodoros` or `until`) is reached.
+    fn bound_reached(&self, until: Option<SystemTime>, now: SystemTime) -> bool {
+        self.cycles.is_some_and(|cycles| self.cycles_done >= cycles)
+            || self
+                .max_pomodoros
+                .is_some_and(|max| self.completed_pomodoros() >= max as usize)
+            || until.is_some_and(|until| now >= until)
+    }
+
+    /// Undo the last transition, restoring the previous step along with its remaining time.
+    ///
+    /// The run left doesn't count on its own anymore: its record is carried over to the next
+    /// run, unless the activity starts over (i.e. it was completed). Its history entry, if any,
+    /// is reversed.
+    ///
+    /// Return the step to start, or `None` if there's no transition to undo.
+    fn back(&mut self, tx_ui: &Sender<UiCommand>) -> Option<usize> {
+        let transition = self.history.pop_back()?;
+        self.pomodoro_count = transition.pomodoro_count;
+        self.timer(transition.step).resume(transition.remaining);
+        let record = self.records.remove(transition.record);
+        self.carried = (!transition.remaining.is_zero()).then_some(record);
+        if let Some(entry) = transition.entry {
+            self.append(
+                HistoryEntry {
+                    undone: true,
+                    ..entry
+                },
+                tx_ui,
+            );
+        }
+
+        Some(transition.step)
+    }
+
+    /// Log `record` of an activity run from `start` to `end` into the history file, if any.
+    ///
+    /// Return the entry logged.
+    fn log(
+        &self,
+        record: &ActivityRecord,
+        start: SystemTime,
+        end: SystemTime,
+        tx_ui: &Sender<UiCommand>,
+    ) -> Option<HistoryEntry> {
+        self.history_file.as_ref()?;
+        let entry = HistoryEntry::new(record, start, end);
+        self.append(entry.clone(), tx_ui);
+
+        Some(entry)
+    }
+
+    /// Append `entry` to the history file, if any.
+    ///
+    /// Logging is best effort: failures are reported to the Ui, never ending the session.
+    fn append(&self, entry: HistoryEntry, tx_ui: &Sender<UiCommand>) {
+        if let Some(history_file) = &self.history_file {
+            if let Err(err) = history_file.append(&entry) {
+                tx_ui
+                    .send(UiCommand::Notice(format!("Unable to save {err}")))
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Context passed to hooks about `record` of `step`.
+    fn hook_env(&self, step: usize, record: &ActivityRecord) -> HookEnv {
+        let pomodoro = match record.activity {
+            Activity::Pomodoro(num) => num,
+            _ => self.pomodoro_count,
+        };
+        let mut env = HookEnv::default()
+            .set("ACTIVITY", record.activity.kind_name())
+            .set("POMODORO", pomodoro)
+            .set("DURATION", record.planned.as_secs());
+        if let Some(label) = &self.sequence[step].label {
+            env = env.set("LABEL", label);
+        }
+        if let Some(task) = &record.task {
+            env = env.set("TASK", task);
+        }
+
+        env
+    }
+
+    /// Start [`Timer`] for `step` and record it.
+    fn run<C: Clock>(
+        &mut self,
+        step: usize,
+        clock: &C,
+        tx_ui: &Sender<UiCommand>,
+        rx_event: &Receiver<Event>,
+    ) -> Result<ActivityRecord> {
+        let activity = self.activity(step);
+        let options = self.timer_options(step);
+        tx_ui
+            .send(UiCommand::Step {
+                label: self.sequence[step].label.clone(),
+                color: self.sequence[step].color,
+            })
+            .unwrap();
+        tx_ui.send(UiCommand::Task(self.task.clone())).unwrap();
+        // Time already spent on an activity gone back to still counts.
+        let record = ActivityRecord {
+            task: self.task.clone(),
+            ..self.carried.take().unwrap_or_else(|| {
+                ActivityRecord::new(activity, self.sequence[step].duration.duration())
+            })
+        };
+        let started_at = clock.wall();
+        let (start_hook, end_hook) = match activity {
+            Activity::Pomodoro(_) => (Hook::PomodoroStart, Hook::PomodoroEnd),
+            _ => (Hook::BreakStart, Hook::BreakEnd),
+        };
+        let env = self.hook_env(step, &record);
+        self.hooks.run(start_hook, &env, tx_ui);
+
+        let pomodoro_count = self.pomodoro_count;
+        let mut state_file = self.state_file.take();
+        let hooks = self.hooks.clone();
+        let mut paused = false;
+        let mut saving_failed = false;
+        let mut checkpoint = |status: &TimerStatus| {
+            match (paused, status) {
+                (false, TimerStatus::Paused(..)) => {
+                    paused = true;
+                    hooks.run(Hook::Pause, &env, tx_ui);
+                }
+                (true, TimerStatus::Running(..)) => {
+                    paused = false;
+                    hooks.run(Hook::Resume, &env, tx_ui);
+                }
+                _ => {}
+            }
+            if let (Some(state_file), Some(state)) = (
+                &mut state_file,
+                SessionState::new(pomodoro_count, step, status, clock.wall()),
+            ) {
+                // Report failures once, until saving works again.
+                match state_file.save(state) {
+                    Err(err) if !saving_failed => {
+                        saving_failed = true;
+                        tx_ui
+                            .send(UiCommand::Notice(format!("Unable to save {err}")))
+                            .unwrap();
+                    }
+                    result => saving_failed = result.is_err(),
+                }
+            }
+        };
+        let record =
+            self.timer(step)
+                .start(record, options, clock, tx_ui, rx_event, &mut checkpoint);
+        self.state_file = state_file;
+
+        let record = record?;
+        // Activities left to be resumed later (going back or quitting) are not over yet.
+        self.logged = match record.outcome {
+            TimerOutcome::Completed | TimerOutcome::Skipped | TimerOutcome::Voided => {
+                self.log(&record, started_at, clock.wall(), tx_ui)
+            }
+            TimerOutcome::Quit | TimerOutcome::Back => None,
+        };
+        // Same goes for hooks ending the activity.
+        let env = self
+            .hook_env(step, &record)
+            .set("OUTCOME", record.outcome.name());
+        if record.outcome == TimerOutcome::Skipped {
+            self.hooks.run(Hook::Skip, &env, tx_ui);
+        }
+        if !matches!(record.outcome, TimerOutcome::Quit | TimerOutcome::Back) {
+            self.hooks.run(end_hook, &env, tx_ui);
+        }
+        // Task may have been changed meanwhile.
+        self.task = record.task.clone();
+        self.records.push(record.clone());
+
+        Ok(record)
+    }
+
+    /// Wait for the user to start `next` step, to skip it if it's a break, or to go back to the
+    /// previous one.
+    ///
+    /// Return the step to start, or `None` if application must be closed.
+    fn confirm<C: Clock>(
+        &mut self,
+        next: usize,
+        clock: &C,
+        tx_ui: &Sender<UiCommand>,
+        rx_event: &Receiver<Event>,
+    ) -> Option<usize> {
+        let activity = self.activity(next);
+        tx_ui
+            .send(UiCommand::Draw(TimerStatus::Waiting(activity)))
+            .unwrap();
         loop {
-            loop {
-                // Increase counter and start pomodoro.
-                self.pomodoro_count += 1;
-                if self.pomodoro.start(
-                    Activity::Pomodoro(self.pomodoro_count),
-                    &tx_ui,
-                    &rx_event,
-                )? {
-                    return Ok(());
-                };
-
-                // Jump to long break every <self.pomodoros> completed pomodoros.
-                if self.pomodoro_count % self.pomodoros == 0 {
-                    break;
+            match clock.recv(rx_event) {
+                Ok(Event::Start | Event::TogglePause | Event::Resume) => return Some(next),
+                Ok(Event::Skip) if !matches!(activity, Activity::Pomodoro(_)) => {
+                    let planned = self.sequence[next].duration.duration();
+                    let record = ActivityRecord {
+                        outcome: TimerOutcome::Skipped,
+                        remaining: planned,
+                        task: self.task.clone(),
+                        ..ActivityRecord::new(activity, planned)
+                    };
+                    let now = clock.wall();
+                    self.logged = self.log(&record, now, now, tx_ui);
+                    let env = self.hook_env(next, &record).set("OUTCOME", "skipped");
+                    self.hooks.run(Hook::Skip, &env, tx_ui);
+                    self.records.push(record.clone());
+                    return Some(self.advance(next, &record));
                 }
+                Ok(Event::Back) => {
+                    if let Some(previous) = self.back(tx_ui) {
+                        return Some(previous);
+                    }
+                }
+                Ok(Event::SetTask(task)) => {
+                    self.task = task;
+                    tx_ui.send(UiCommand::Task(self.task.clone())).unwrap();
+                }
+                // EventHandler disconnected, close application.
+                Ok(Event::Quit) | Err(RecvError) => return None,
+                Ok(_) => {}
+            }
+        }
+    }
+
+    /// Resume [`Session`] from `state` left by a previous run, unless older than
+    /// `resume_cutoff` or not matching the sequence anymore.
+    ///
+    /// The wall-clock time elapsed since `state` was saved counts toward the activity if it was
+    /// running: if its timer expired meanwhile, the session moves on to the next step.
+    ///
+    /// Return whether the session has been resumed.
+    pub fn resume(&mut self, state: &SessionState, now: SystemTime) -> bool {
+        self.init_sequence();
+        let age = state.age(now);
+        if age > self.resume_cutoff.duration() || state.step >= self.sequence.len() {
+            return false;
+        }
+        // Nothing is changed unless resumed.
+        let matching = match (self.sequence[state.step].kind, state.activity) {
+            (ActivityKind::Pomodoro, Activity::Pomodoro(num)) => num == state.pomodoro_count + 1,
+            (ActivityKind::ShortBreak, Activity::ShortBreak)
+            | (ActivityKind::LongBreak, Activity::LongBreak) => true,
+            _ => false,
+        };
+        if !matching {
+            return false;
+        }
+
+        self.pomodoro_count = state.pomodoro_count;
+
+        let mut remaining = Duration::from_secs(state.remaining);
+        if !state.paused {
+            remaining = remaining.saturating_sub(age);
+        }
+
+        let step = match state.activity {
+            _ if !remaining.is_zero() => {
+                self.timer(state.step).resume(remaining);
+                state.step
+            }
+            Activity::Pomodoro(num) => {
+                self.pomodoro_count = num;
+                self.next(state.step)
+            }
+            _ => self.next(state.step),
+        };
+        self.resumed = Some(step);
+
+        true
+    }
+
+    /// Start [`Session`], using `clock` as source of time.
+    pub fn start<C: Clock>(
+        &mut self,
+        clock: &C,
+        tx_ui: Sender<UiCommand>,
+        rx_event: Receiver<Event>,
+    ) -> Result<()> {
+        let result = self.run_steps(clock, &tx_ui, &rx_event);
+
+        let mut env = HookEnv::default()
+            .set("POMODORO", self.completed_pomodoros())
+            .set("FOCUS_TIME", self.focus_time().as_secs());
+        if let Some(task) = &self.task {
+            env = env.set("TASK", task);
+        }
+        // Let the last hook finish before the application exits.
+        if let Some(hook) = self.hooks.run(Hook::SessionEnd, &env, &tx_ui) {
+            hook.join().unwrap();
+        }
 
-                // Start short break.
-                if self
-                    .short_break
-                    .start(Activity::ShortBreak, &tx_ui, &rx_event)?
-                {
-                    return Ok(());
-                };
+        result
+    }
+
+    /// Run steps one after the other, until the session ends.
+    fn run_steps<C: Clock>(
+        &mut self,
+        clock: &C,
+        tx_ui: &Sender<UiCommand>,
+        rx_event: &Receiver<Event>,
+    ) -> Result<()> {
+        self.init_sequence();
+        self.load_goal_base(clock.wall(), tx_ui);
+        self.update_goal(tx_ui)?;
+        let until = self.until.map(|until| until.next_after(clock.wall()));
+        let mut step = self.resumed.take().unwrap_or_default();
+        loop {
+            let record = self.run(step, clock, tx_ui, rx_event)?;
+            step = match (record.outcome, record.activity) {
+                (TimerOutcome::Quit, _) => return Ok(()),
+                // With nothing to go back to, resume the current step where it was left.
+                (TimerOutcome::Back, _) => self.back(tx_ui).unwrap_or_else(|| {
+                    self.timer(step).resume(record.remaining);
+                    step
+                }),
+                // Voided pomodoros are not counted toward the long break: start it over.
+                (TimerOutcome::Voided, Activity::Pomodoro(_)) => step,
+                (_, Activity::Pomodoro(num)) => {
+                    let next = self.advance(step, &record);
+                    self.pomodoro_count = num;
+                    next
+                }
+                _ => self.advance(step, &record),
+            };
+            if record.quit {
+                return Ok(());
             }
-            // Start long break.
-            if self
-                .long_break
-                .start(Activity::LongBreak, &tx_ui, &rx_event)?
+
+            if (self.update_goal(tx_ui)? && self.stop_at_goal)
+                || self.bound_reached(until, clock.wall())
             {
                 return Ok(());
-            };
+            }
+
+            // Overtime is ended by the user, which already confirms moving on.
+            if !self.auto_start(step) && record.overtime.is_zero() {
+                match self.confirm(step, clock, tx_ui, rx_event) {
+                    // Skipping the last step may complete a cycle.
+                    Some(_) if self.bound_reached(until, clock.wall()) => return Ok(()),
+                    Some(next) => step = next,
+                    None => return Ok(()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::{sync::mpsc, time::Duration};
+
+    /// Run `session` on a [`ManualClock`] until `secs` seconds have elapsed, sending `events` at
+    /// the given seconds, and return the sequence of started activities.
+    fn run_with_events(
+        session: &mut Session,
+        secs: u64,
+        events: impl IntoIterator<Item = (u64, Event)>,
+    ) -> Vec<Activity> {
+        let clock = ManualClock::new();
+        let (tx_ui, rx_ui) = mpsc::channel();
+        let (tx_event, rx_event) = mpsc::channel::<Event>();
+        for (at, event) in events {
+            let tx_event = tx_event.clone();
+            clock.schedule(Duration::from_secs(at), move || {
+                tx_event.send(event).unwrap()
+            });
         }
+        // Quit application by disconnecting events.
+        clock.schedule(Duration::from_secs(secs), move || drop(tx_event));
+
+        session.notifications = false;
+        session.start(&clock, tx_ui, rx_event).unwrap();
+
+        let mut activities: Vec<Activity> = vec![];
+        for ui_command in rx_ui {
+            if let UiCommand::Draw(TimerStatus::Running(activity, _)) = ui_command {
+                if activities.last() != Some(&activity) {
+                    activities.push(activity);
+                }
+            }
+        }
+
+        activities
+    }
+
+    /// Run `session` on a [`ManualClock`] until `secs` seconds have elapsed, returning the
+    /// sequence of started activities.
+    fn run(session: &mut Session, secs: u64) -> Vec<Activity> {
+        run_with_events(session, secs, [])
+    }
+
+    #[test]
+    /// Next activity starting only once the expired screen is over.
+    fn session_transition_time() {
+        // Pomodoro (25m) followed by expired screen (5s).
+        let activities = run(&mut Session::default(), 25 * 60 + 5);
+        assert_eq!(activities, [Activity::Pomodoro(1)]);
+        // Quitting from the expired screen still completes the pomodoro.
+        let mut session = Session::default();
+        run(&mut session, 25 * 60 + 2);
+        assert_eq!(session.records[0].outcome, TimerOutcome::Completed);
+        assert_eq!(session.completed_pomodoros(), 1);
+
+        let activities = run(&mut Session::default(), 25 * 60 + 6);
+        assert_eq!(activities, [Activity::Pomodoro(1), Activity::ShortBreak]);
+    }
+
+    #[test]
+    /// Full pomodoro cycle, ending with a long break.
+    fn session_cycle() {
+        // 4 pomodoros, 3 short breaks, 1 long break and 8 expired screens.
+        let cycle = (4 * 25 + 3 * 5 + 15) * 60 + 8 * 5;
+        let mut session = Session::default();
+        let activities = run(&mut session, cycle + 1);
+        assert_eq!(
+            activities,
+            [
+                Activity::Pomodoro(1),
+                Activity::ShortBreak,
+                Activity::Pomodoro(2),
+                Activity::ShortBreak,
+                Activity::Pomodoro(3),
+                Activity::ShortBreak,
+                Activity::Pomodoro(4),
+                Activity::LongBreak,
+                Activity::Pomodoro(5),
+            ]
+        );
+        // Pomodoro #5 running for 1 second before quitting.
+        assert_eq!(session.focus_time(), Duration::from_secs(4 * 25 * 60 + 1));
+    }
+
+    #[test]
+    /// Breaks waiting for confirmation before starting.
+    fn session_manual_start() {
+        let mut session = Session {
+            auto_start_breaks: false,
+            ..Default::default()
+        };
+
+        // Short break started at 30m, lasting 5m plus expired screen.
+        let events = || [(30 * 60, Event::Start)];
+        let activities = run_with_events(&mut session.clone(), 35 * 60 + 5, events());
+        assert_eq!(activities, [Activity::Pomodoro(1), Activity::ShortBreak]);
+        let activities = run_with_events(&mut session.clone(), 35 * 60 + 6, events());
+        assert_eq!(
+            activities,
+            [
+                Activity::Pomodoro(1),
+                Activity::ShortBreak,
+                Activity::Pomodoro(2)
+            ]
+        );
+
+        // Short break skipped.
+        let events = [(26 * 60, Event::Skip)];
+        let activities = run_with_events(&mut session, 26 * 60 + 1, events);
+        assert_eq!(activities, [Activity::Pomodoro(1), Activity::Pomodoro(2)]);
+        let outcomes: Vec<_> = session.records.iter().map(|r| r.outcome).collect();
+        assert_eq!(
+            outcomes,
+            [
+                TimerOutcome::Completed,
+                TimerOutcome::Skipped,
+                TimerOutcome::Quit
+            ]
+        );
+    }
+
+    #[test]
+    /// Voided pomodoro started over and not counted.
+    fn session_void() {
+        let mut session = Session::default();
+        let activities = run_with_events(&mut session, 10 * 60 + 1, [(10 * 60, Event::Void)]);
+        assert_eq!(activities, [Activity::Pomodoro(1)]);
+        assert_eq!(session.pomodoro_count, 0);
+        assert_eq!(session.records[0].outcome, TimerOutcome::Voided);
+        // Only the pomodoro started over counts.
+        assert_eq!(session.focus_time(), Duration::from_secs(1));
+    }
+
+    #[test]
+    /// Task recorded with activities, changed while running.
+    fn session_task() {
+        let mut session = Session {
+            task: Some("Write report".to_string()),
+            ..Default::default()
+        };
+        let events = [(10 * 60, Event::SetTask(Some("Review".to_string())))];
+        run_with_events(&mut session, 25 * 60 + 6, events);
+        let tasks: Vec<_> = session.records.iter().map(|r| r.task.as_deref()).collect();
+        // Task set when the pomodoro ends carried over to the break.
+        assert_eq!(tasks, [Some("Review"), Some("Review")]);
+        assert_eq!(session.task.as_deref(), Some("Review"));
+    }
+
+    #[test]
+    /// Going back to the previous activity with its remaining time.
+    fn session_back() {
+        let dir = std::env::temp_dir().join(format!("solanum-back-{}", std::process::id()));
+        let history_file = HistoryFile::with_path(dir.join("history.jsonl"));
+        let mut session = Session {
+            history_file: Some(history_file.clone()),
+            ..Default::default()
+        };
+        let events = [(10 * 60, Event::Skip), (11 * 60, Event::Back)];
+        // Pomodoro #1 skipped at 10m and resumed at 11m, with 15m left.
+        let activities = run_with_events(&mut session, 26 * 60 + 6, events);
+        assert_eq!(
+            activities,
+            [
+                Activity::Pomodoro(1),
+                Activity::ShortBreak,
+                Activity::Pomodoro(1),
+                Activity::ShortBreak,
+            ]
+        );
+        assert_eq!(session.pomodoro_count, 1);
+        // Skipped run carried over to the resumed one.
+        let outcomes: Vec<_> = session.records.iter().map(|r| r.outcome).collect();
+        assert_eq!(
+            outcomes,
+            [
+                TimerOutcome::Back,
+                TimerOutcome::Completed,
+                TimerOutcome::Quit
+            ]
+        );
+        assert_eq!(session.records[1].actual, Duration::from_secs(25 * 60));
+        assert_eq!(session.focus_time(), Duration::from_secs(25 * 60));
+        // Skipped run logged, then reversed.
+        let entries = history_file.load().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].outcome, TimerOutcome::Completed);
+        assert_eq!(entries[0].actual, 25 * 60);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Completed pomodoro gone back to from the waiting screen: started over, counted once.
+        let mut session = Session {
+            auto_start_breaks: false,
+            history_file: Some(history_file.clone()),
+            ..Default::default()
+        };
+        let activities = run_with_events(&mut session, 51 * 60 + 1, [(26 * 60, Event::Back)]);
+        assert_eq!(activities, [Activity::Pomodoro(1)]);
+        assert_eq!(session.pomodoro_count, 1);
+        assert_eq!(session.completed_pomodoros(), 1);
+        assert_eq!(session.focus_time(), Duration::from_secs(25 * 60));
+        assert_eq!(history_file.load().unwrap().len(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+
+        // Nothing to go back to: current activity goes on.
+        let mut session = Session::default();
+        let activities = run_with_events(&mut session, 25 * 60 + 6, [(60, Event::Back)]);
+        assert_eq!(activities, [Activity::Pomodoro(1), Activity::ShortBreak]);
+    }
+
+    #[test]
+    /// Session resumed from the state left by a previous run.
+    fn session_resume() {
+        let now = SystemTime::now();
+        let state = SessionState {
+            saved_at: (now - Duration::from_secs(5 * 60))
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            pomodoro_count: 1,
+            step: 2,
+            remaining: 10 * 60,
+            paused: false,
+            activity: Activity::Pomodoro(2),
+        };
+
+        // Running pomodoro: 5m left.
+        let mut session = Session::default();
+        assert!(session.resume(&state, now));
+        let activities = run(&mut session, 5 * 60 + 6);
+        assert_eq!(activities, [Activity::Pomodoro(2), Activity::ShortBreak]);
+        assert_eq!(session.pomodoro_count, 2);
+
+        // Paused pomodoro: 10m left.
+        let mut session = Session::default();
+        assert!(session.resume(
+            &SessionState {
+                paused: true,
+                ..state
+            },
+            now
+        ));
+        let activities = run(&mut session, 10 * 60 + 5);
+        assert_eq!(activities, [Activity::Pomodoro(2)]);
+
+        // Pomodoro expired meanwhile.
+        let mut session = Session::default();
+        let state = SessionState {
+            remaining: 60,
+            ..state
+        };
+        assert!(session.resume(&state, now));
+        assert_eq!(run(&mut session, 1), [Activity::ShortBreak]);
+        assert_eq!(session.pomodoro_count, 2);
+
+        // State too old.
+        let mut session = Session::default();
+        assert!(!session.resume(&state, now + Duration::from_secs(2 * 3600)));
+        assert_eq!(run(&mut session, 1), [Activity::Pomodoro(1)]);
+
+        // Activity not matching the sequence anymore: session left untouched.
+        let mut session = Session::default();
+        let state = SessionState {
+            activity: Activity::LongBreak,
+            ..state
+        };
+        assert!(!session.resume(&state, now));
+        assert_eq!(run(&mut session, 1), [Activity::Pomodoro(1)]);
+    }
+
+    #[test]
+    /// Session running on when its state and history can't be saved.
+    fn session_unsaved() {
+        // No directory can be created under a regular file.
+        let mut session = Session {
+            state_file: Some(StateFile::with_path("/dev/null/state.toml".into())),
+            history_file: Some(HistoryFile::with_path("/dev/null/history.jsonl".into())),
+            ..Default::default()
+        };
+        let activities = run(&mut session, 25 * 60 + 6);
+        assert_eq!(activities, [Activity::Pomodoro(1), Activity::ShortBreak]);
+        assert_eq!(session.completed_pomodoros(), 1);
+    }
+
+    #[test]
+    /// Custom sequence, looping over its steps.
+    fn session_sequence() {
+        let mut session: Session = toml::from_str(
+            r#"
+            [[sequence]]
+            kind = "pomodoro"
+            duration = "52m"
+            label = "Deep work"
+            color = "blue"
+
+            [[sequence]]
+            kind = "short_break"
+            duration = "17m"
+            "#,
+        )
+        .unwrap();
+        let activities = run(&mut session, (52 + 17) * 60 + 2 * 5 + 1);
+        assert_eq!(
+            activities,
+            [
+                Activity::Pomodoro(1),
+                Activity::ShortBreak,
+                Activity::Pomodoro(2)
+            ]
+        );
+        assert_eq!(session.sequence[0].label.as_deref(), Some("Deep work"));
+        assert_eq!(session.focus_time(), Duration::from_secs(52 * 60 + 1));
+    }
+
+    #[test]
+    /// Session ending once the daily goal is reached.
+    fn session_goal() {
+        let mut session: Session = toml::from_str("daily_goal = 2\nstop_at_goal = true").unwrap();
+        assert!(matches!(session.daily_goal, Some(Goal::Pomodoros(2))));
+        let activities = run(&mut session, 24 * 3600);
+        assert_eq!(
+            activities,
+            [
+                Activity::Pomodoro(1),
+                Activity::ShortBreak,
+                Activity::Pomodoro(2)
+            ]
+        );
+        assert_eq!(session.completed_pomodoros(), 2);
+
+        // Goal reached, but going on.
+        let mut session: Session = toml::from_str(r#"daily_goal = "30m""#).unwrap();
+        assert!(matches!(session.daily_goal, Some(Goal::FocusTime(_))));
+        // 2 pomodoros, 1 short break and 3 expired screens.
+        let activities = run(&mut session, (2 * 25 + 5) * 60 + 3 * 5 + 1);
+        assert_eq!(activities.len(), 4);
+        assert!(session.goal_reached);
+
+        // Ambiguous or out of range goals rejected.
+        for goal in ["300", r#""8""#, "1.5"] {
+            assert!(toml::from_str::<Session>(&format!("daily_goal = {goal}")).is_err());
+        }
+
+        // Progress made earlier today counted in.
+        let dir = std::env::temp_dir().join(format!("solanum-goal-{}", std::process::id()));
+        let history_file = HistoryFile::with_path(dir.join("history.jsonl"));
+        let record = ActivityRecord::new(Activity::Pomodoro(1), Duration::from_secs(25 * 60));
+        let now = SystemTime::now();
+        history_file
+            .append(&HistoryEntry::new(&record, now, now))
+            .unwrap();
+        let mut session: Session = toml::from_str("daily_goal = 2\nstop_at_goal = true").unwrap();
+        session.history_file = Some(history_file);
+        let activities = run(&mut session, 24 * 3600);
+        assert_eq!(activities, [Activity::Pomodoro(1)]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    /// Session ending after a number of cycles or pomodoros, or at a time of the day.
+    fn session_bounds() {
+        let mut session = Session {
+            cycles: Some(1),
+            ..Default::default()
+        };
+        let activities = run(&mut session, 24 * 3600);
+        assert_eq!(activities.len(), 8);
+        assert_eq!(activities.last(), Some(&Activity::LongBreak));
+
+        let mut session = Session {
+            max_pomodoros: Some(2),
+            ..Default::default()
+        };
+        let activities = run(&mut session, 24 * 3600);
+        assert_eq!(activities.last(), Some(&Activity::Pomodoro(2)));
+
+        // Long break skipped while waiting to start it, completing the cycle.
+        let mut session = Session {
+            pomodoros: 1,
+            cycles: Some(1),
+            auto_start_breaks: false,
+            ..Default::default()
+        };
+        let activities = run_with_events(&mut session, 24 * 3600, [(26 * 60, Event::Skip)]);
+        assert_eq!(activities, [Activity::Pomodoro(1)]);
+
+        // Pomodoro #3 starting before the time of the day, and ending after it.
+        let until = chrono::Local::now() + chrono::Duration::minutes(70);
+        let mut session = Session {
+            until: Some(until.format("%H:%M").to_string().parse().unwrap()),
+            ..Default::default()
+        };
+        let activities = run(&mut session, 24 * 3600);
+        assert_eq!(activities.last(), Some(&Activity::Pomodoro(3)));
     }
 }
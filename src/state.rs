@@ -0,0 +1,178 @@
+use crate::{error::Error, session::Activity, timer::TimerStatus, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Minimum interval between two saves of an unchanged [`SessionState`] (i.e. only the remaining
+/// time went down).
+const SAVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Snapshot of the in-progress [`Session`](crate::session::Session), allowing to resume it after
+/// a restart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionState {
+    /// Wall-clock time of the snapshot, as seconds since UNIX epoch.
+    pub saved_at: u64,
+    /// Count of completed pomodoros.
+    pub pomodoro_count: u8,
+    /// Index of the current step in the sequence.
+    #[serde(default)]
+    pub step: usize,
+    /// Remaining time of the current activity, in seconds.
+    pub remaining: u64,
+    /// Whether the current activity was paused.
+    pub paused: bool,
+    /// Current activity.
+    pub activity: Activity,
+}
+
+impl SessionState {
+    /// Construct [`SessionState`] from `status` of the running timer, if it's worth saving.
+    pub fn new(
+        pomodoro_count: u8,
+        step: usize,
+        status: &TimerStatus,
+        now: SystemTime,
+    ) -> Option<Self> {
+        let (activity, timer, paused) = match status {
+            TimerStatus::Running(activity, timer) => (*activity, timer, false),
+            TimerStatus::Paused(activity, timer, _) => (*activity, timer, true),
+            _ => return None,
+        };
+
+        Some(Self {
+            saved_at: now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            pomodoro_count,
+            step,
+            remaining: timer.remaining().as_secs(),
+            paused,
+            activity,
+        })
+    }
+
+    /// Time elapsed since the snapshot.
+    pub fn age(&self, now: SystemTime) -> Duration {
+        now.duration_since(UNIX_EPOCH + Duration::from_secs(self.saved_at))
+            .unwrap_or_default()
+    }
+
+    /// Whether `other` only differs by the passing of time.
+    fn same_as(&self, other: &Self) -> bool {
+        let same_remaining = if self.paused {
+            self.remaining == other.remaining
+        } else {
+            // Expiry time may be off by one because of rounding to whole seconds.
+            (self.saved_at + self.remaining).abs_diff(other.saved_at + other.remaining) <= 1
+        };
+
+        self.activity == other.activity
+            && self.pomodoro_count == other.pomodoro_count
+            && self.step == other.step
+            && self.paused == other.paused
+            && same_remaining
+    }
+}
+
+/// File holding the last [`SessionState`].
+#[derive(Debug, Clone)]
+pub struct StateFile {
+    /// File path.
+    path: PathBuf,
+    /// Last saved state.
+    last: Option<SessionState>,
+}
+
+impl StateFile {
+    /// Construct [`StateFile`] in the XDG state directory (or data directory as a fallback), if
+    /// any.
+    pub fn new() -> Option<Self> {
+        dirs::state_dir()
+            .or_else(dirs::data_dir)
+            .map(|dir| Self::with_path(dir.join("solanum/state.toml")))
+    }
+
+    /// Construct [`StateFile`] at `path`.
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path, last: None }
+    }
+
+    /// Construct [`Error::State`] for this file.
+    fn error(&self, err: impl ToString) -> Error {
+        Error::State(self.path.clone(), err.to_string())
+    }
+
+    /// Load last saved [`SessionState`], if any.
+    pub fn load(&self) -> Result<Option<SessionState>> {
+        match fs::read_to_string(&self.path) {
+            Ok(state) => Ok(Some(toml::from_str(&state).map_err(|err| self.error(err))?)),
+            Err(_) if !self.path.exists() => Ok(None),
+            Err(err) => Err(self.error(err)),
+        }
+    }
+
+    /// Save `state`, unless it only differs from the last saved one by the passing of time and
+    /// [`SAVE_INTERVAL`] hasn't elapsed yet.
+    ///
+    /// Since a running timer keeps running while solanum is not, skipping intermediate saves
+    /// loses nothing: the state file is rewritten atomically only when something changed.
+    pub fn save(&mut self, state: SessionState) -> Result<()> {
+        if let Some(last) = self.last {
+            if last.same_as(&state) && state.saved_at < last.saved_at + SAVE_INTERVAL.as_secs() {
+                return Ok(());
+            }
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|err| self.error(err))?;
+        }
+        let content = toml::to_string(&state).map_err(|err| self.error(err))?;
+        // Write to temporary file first, so that the state file is never left half-written.
+        let tmp = self.path.with_extension("toml.tmp");
+        fs::write(&tmp, content).map_err(|err| self.error(err))?;
+        fs::rename(&tmp, &self.path).map_err(|err| self.error(err))?;
+        self.last = Some(state);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::timer::Timer;
+
+    #[test]
+    /// State saved and loaded back, skipping saves with nothing new.
+    fn state_save_load() {
+        let dir = std::env::temp_dir().join(format!("solanum-state-{}", std::process::id()));
+        let mut file = StateFile::with_path(dir.join("state.toml"));
+        assert_eq!(file.load().unwrap(), None);
+
+        let now = SystemTime::now();
+        let status = TimerStatus::Running(Activity::Pomodoro(2), Timer::new(0, 25, 0));
+        let state = SessionState::new(1, 2, &status, now).unwrap();
+        file.save(state).unwrap();
+        assert_eq!(file.load().unwrap(), Some(state));
+
+        // One second later, one second less: not saved.
+        let later = SessionState {
+            saved_at: state.saved_at + 1,
+            remaining: state.remaining - 1,
+            ..state
+        };
+        file.save(later).unwrap();
+        assert_eq!(file.load().unwrap(), Some(state));
+
+        // Breaks are saved as well.
+        let status =
+            TimerStatus::Paused(Activity::LongBreak, Timer::new(0, 10, 0), Timer::default());
+        let state = SessionState::new(4, 7, &status, now).unwrap();
+        file.save(state).unwrap();
+        assert_eq!(file.load().unwrap(), Some(state));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,401 @@
+use crate::{
+    args::{DateRange, Period, StatsArgs},
+    error::Error,
+    history::{HistoryEntry, HistoryFile},
+    session::Activity,
+    timer::{Timer, TimerOutcome},
+    Result,
+};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display},
+};
+
+/// Number of weeks shown in the heatmap, unless `--since` is given.
+const HEATMAP_WEEKS: i64 = 26;
+
+/// Heatmap cells, by increasing number of completed pomodoros.
+const HEATMAP_LEVELS: [char; 5] = ['·', '░', '▒', '▓', '█'];
+
+/// Pomodoro counts and focus time over a set of activities.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    /// Completed pomodoros.
+    pub completed: usize,
+    /// Skipped pomodoros.
+    pub skipped: usize,
+    /// Voided pomodoros.
+    pub voided: usize,
+    /// Focus time (voided pomodoros excluded), in seconds.
+    pub focus_time: u64,
+}
+
+impl Summary {
+    /// Summary of the pomodoros among `entries` started on `day`.
+    pub fn of_day(entries: &[HistoryEntry], day: NaiveDate) -> Self {
+        let mut summary = Self::default();
+        entries
+            .iter()
+            .filter(|entry| {
+                matches!(entry.activity, Activity::Pomodoro(_)) && entry.start.date_naive() == day
+            })
+            .for_each(|entry| summary.add(entry));
+
+        summary
+    }
+
+    /// Count pomodoro `entry` in.
+    fn add(&mut self, entry: &HistoryEntry) {
+        match entry.outcome {
+            TimerOutcome::Completed => self.completed += 1,
+            TimerOutcome::Skipped => self.skipped += 1,
+            TimerOutcome::Voided => self.voided += 1,
+            // Not logged anyway.
+            TimerOutcome::Back | TimerOutcome::Quit => {}
+        }
+        if entry.outcome != TimerOutcome::Voided {
+            self.focus_time += entry.actual + entry.overtime;
+        }
+    }
+
+    /// Percentage of pomodoros out of the started ones, if any.
+    fn rate(&self, count: usize) -> Option<usize> {
+        let started = self.completed + self.skipped + self.voided;
+        (started > 0).then(|| count * 100 / started)
+    }
+
+    /// Write summary as a row of the table printed by [`Stats`].
+    fn write_row(&self, f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+        let rate = |rate: Option<usize>| rate.map_or("-".to_string(), |rate| format!("{rate}%"));
+        writeln!(
+            f,
+            "{:<12} {:>9} {:>11} {:>9} {:>7}",
+            name,
+            self.completed,
+            Timer::new(0, 0, self.focus_time as usize).to_string(),
+            rate(self.rate(self.completed)),
+            rate(self.rate(self.skipped)),
+        )
+    }
+}
+
+/// [`Summary`] of a day, week or month.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PeriodSummary {
+    /// Period (e.g. `2022-11-21`, `2022-W47` or `2022-11`).
+    pub period: String,
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+/// [`Summary`] of a task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaskSummary {
+    /// Task, if any.
+    pub task: Option<String>,
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+/// Consecutive days with at least one completed pomodoro.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Streaks {
+    /// Streak going on (today doesn't break it until it's over).
+    pub current: usize,
+    /// Longest streak.
+    pub longest: usize,
+}
+
+/// Statistics out of the activity history.
+#[derive(Debug, Clone, Serialize)]
+pub struct Stats {
+    /// Summary of the whole range.
+    pub total: Summary,
+    /// Summary of each period.
+    pub periods: Vec<PeriodSummary>,
+    /// Summary of each task, by decreasing focus time.
+    pub tasks: Vec<TaskSummary>,
+    pub streaks: Streaks,
+    /// Period statistics are grouped by.
+    #[serde(skip)]
+    by: Period,
+    /// Completed pomodoros per day.
+    #[serde(skip)]
+    days: BTreeMap<NaiveDate, usize>,
+    /// First day of the heatmap.
+    #[serde(skip)]
+    first: NaiveDate,
+    /// Last day of the range.
+    #[serde(skip)]
+    last: NaiveDate,
+}
+
+impl Stats {
+    /// Compute statistics of pomodoros in `entries` started within `range`, grouped `by` period,
+    /// as of `today`.
+    pub fn new(entries: &[HistoryEntry], range: DateRange, by: Period, today: NaiveDate) -> Self {
+        let mut total = Summary::default();
+        let mut periods: BTreeMap<String, Summary> = BTreeMap::new();
+        let mut tasks: BTreeMap<Option<String>, Summary> = BTreeMap::new();
+        let mut days: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+
+        for entry in entries {
+            let date = entry.start.date_naive();
+            if !matches!(entry.activity, Activity::Pomodoro(_)) || !range.contains(date) {
+                continue;
+            }
+
+            let period = match by {
+                Period::Day => date.format("%Y-%m-%d").to_string(),
+                Period::Week => {
+                    let week = date.iso_week();
+                    format!("{}-W{:02}", week.year(), week.week())
+                }
+                Period::Month => date.format("%Y-%m").to_string(),
+            };
+            total.add(entry);
+            periods.entry(period).or_default().add(entry);
+            tasks.entry(entry.task.clone()).or_default().add(entry);
+            if entry.outcome == TimerOutcome::Completed {
+                *days.entry(date).or_default() += 1;
+            }
+        }
+
+        let last = range.until.map_or(today, |until| until.min(today));
+        let first = range
+            .since
+            .unwrap_or_else(|| last - Duration::weeks(HEATMAP_WEEKS) + Duration::days(1));
+
+        let mut tasks: Vec<_> = tasks
+            .into_iter()
+            .map(|(task, summary)| TaskSummary { task, summary })
+            .collect();
+        tasks.sort_by_key(|task| std::cmp::Reverse(task.summary.focus_time));
+
+        Self {
+            total,
+            periods: periods
+                .into_iter()
+                .map(|(period, summary)| PeriodSummary { period, summary })
+                .collect(),
+            tasks,
+            streaks: streaks(days.keys().copied().collect(), last),
+            by,
+            days,
+            first,
+            last,
+        }
+    }
+
+    /// Write GitHub-style calendar heatmap of completed pomodoros: one column per week, one row
+    /// per day of the week.
+    fn write_heatmap(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Weeks start on Monday.
+        let start = self.first - Duration::days(self.first.weekday().num_days_from_monday() as i64);
+        let weeks = (self.last - start).num_weeks() + 1;
+
+        for (weekday, name) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+            write!(f, "{name:<4}")?;
+            for week in 0..weeks {
+                let date = start + Duration::weeks(week) + Duration::days(weekday as i64);
+                if date < self.first || date > self.last {
+                    write!(f, "  ")?;
+                    continue;
+                }
+                let level = match self.days.get(&date).copied().unwrap_or_default() {
+                    0 => 0,
+                    1..=2 => 1,
+                    3..=4 => 2,
+                    5..=7 => 3,
+                    _ => 4,
+                };
+                write!(f, "{} ", HEATMAP_LEVELS[level])?;
+            }
+            writeln!(f)?;
+        }
+
+        write!(f, "    Less ")?;
+        for level in HEATMAP_LEVELS {
+            write!(f, "{level} ")?;
+        }
+        writeln!(f, "More")
+    }
+}
+
+/// Compute [`Streaks`] out of the set of `days` with completed pomodoros, up to `last` day.
+fn streaks(days: BTreeSet<NaiveDate>, last: NaiveDate) -> Streaks {
+    let mut streaks = Streaks::default();
+    let mut streak = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in days.iter().filter(|&&day| day <= last) {
+        streak = match previous {
+            Some(previous) if day - previous == Duration::days(1) => streak + 1,
+            _ => 1,
+        };
+        streaks.longest = streaks.longest.max(streak);
+        previous = Some(day);
+    }
+
+    // The last day doesn't break the streak until it's over.
+    let mut day = if days.contains(&last) {
+        last
+    } else {
+        last - Duration::days(1)
+    };
+    while days.contains(&day) {
+        streaks.current += 1;
+        day -= Duration::days(1);
+    }
+
+    streaks
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let period = match self.by {
+            Period::Day => "Day",
+            Period::Week => "Week",
+            Period::Month => "Month",
+        };
+        writeln!(
+            f,
+            "{:<12} {:>9} {:>11} {:>9} {:>7}",
+            period, "Pomodoros", "Focus time", "Completed", "Skipped"
+        )?;
+        for period in &self.periods {
+            period.summary.write_row(f, &period.period)?;
+        }
+        self.total.write_row(f, "Total")?;
+
+        let days = |num| if num == 1 { "day" } else { "days" };
+        writeln!(
+            f,
+            "\nStreak: {} {} (longest: {} {})",
+            self.streaks.current,
+            days(self.streaks.current),
+            self.streaks.longest,
+            days(self.streaks.longest)
+        )?;
+
+        if !self.tasks.is_empty() {
+            writeln!(
+                f,
+                "\n{:<32} {:>9} {:>11}",
+                "Task", "Pomodoros", "Focus time"
+            )?;
+            for task in &self.tasks {
+                writeln!(
+                    f,
+                    "{:<32} {:>9} {:>11}",
+                    task.task.as_deref().unwrap_or("(no task)"),
+                    task.summary.completed,
+                    Timer::new(0, 0, task.summary.focus_time as usize).to_string(),
+                )?;
+            }
+        }
+
+        writeln!(f)?;
+        self.write_heatmap(f)
+    }
+}
+
+/// Print statistics out of the activity history, according to `args`.
+pub fn print(args: &StatsArgs) -> Result<()> {
+    let history_file = HistoryFile::new().ok_or("unable to locate data directory")?;
+    let entries = history_file.load()?;
+    let stats = Stats::new(&entries, args.range, args.by, Local::now().date_naive());
+
+    if args.json {
+        let json =
+            serde_json::to_string_pretty(&stats).map_err(|err| Error::Other(err.to_string()))?;
+        println!("{json}");
+    } else {
+        print!("{stats}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::session::ActivityRecord;
+    use chrono::{Local, TimeZone};
+
+    /// History entry of `activity` ended with `outcome`, started on `day` of November 2022 and
+    /// lasting 25 minutes.
+    fn entry(
+        day: u32,
+        activity: Activity,
+        outcome: TimerOutcome,
+        task: Option<&str>,
+    ) -> HistoryEntry {
+        let start = Local.with_ymd_and_hms(2022, 11, day, 9, 0, 0).unwrap();
+        let duration = std::time::Duration::from_secs(25 * 60);
+        let record = ActivityRecord {
+            outcome,
+            actual: duration,
+            task: task.map(str::to_string),
+            ..ActivityRecord::new(activity, duration)
+        };
+        HistoryEntry::new(
+            &record,
+            start.into(),
+            (start + Duration::minutes(25)).into(),
+        )
+    }
+
+    #[test]
+    /// Statistics by day and task, with streaks.
+    fn stats() {
+        let pomodoro = Activity::Pomodoro(1);
+        let entries = [
+            entry(1, pomodoro, TimerOutcome::Completed, Some("Report")),
+            entry(2, pomodoro, TimerOutcome::Completed, Some("Report")),
+            entry(2, Activity::ShortBreak, TimerOutcome::Completed, None),
+            entry(2, pomodoro, TimerOutcome::Skipped, None),
+            entry(3, pomodoro, TimerOutcome::Voided, Some("Report")),
+            entry(4, pomodoro, TimerOutcome::Completed, None),
+            entry(5, pomodoro, TimerOutcome::Completed, None),
+        ];
+        let range = DateRange {
+            since: None,
+            until: None,
+        };
+        let today = NaiveDate::from_ymd_opt(2022, 11, 6).unwrap();
+        let stats = Stats::new(&entries, range, Period::Day, today);
+
+        assert_eq!(stats.total.completed, 4);
+        assert_eq!(stats.total.skipped, 1);
+        assert_eq!(stats.total.voided, 1);
+        // Breaks and voided pomodoros don't count.
+        assert_eq!(stats.total.focus_time, 5 * 25 * 60);
+        assert_eq!(stats.total.rate(stats.total.completed), Some(66));
+        assert_eq!(stats.periods.len(), 5);
+        assert_eq!(stats.periods[1].period, "2022-11-02");
+        assert_eq!(stats.periods[1].summary.skipped, 1);
+        // Today not over yet: streak still going on.
+        assert_eq!(
+            stats.streaks,
+            Streaks {
+                current: 2,
+                longest: 2
+            }
+        );
+        assert_eq!(stats.tasks[0].task, None);
+        assert_eq!(stats.tasks[0].summary.focus_time, 3 * 25 * 60);
+
+        // Filtered by range, grouped by week.
+        let range = DateRange {
+            since: NaiveDate::from_ymd_opt(2022, 11, 2),
+            until: NaiveDate::from_ymd_opt(2022, 11, 3),
+        };
+        let stats = Stats::new(&entries, range, Period::Week, today);
+        assert_eq!(stats.total.completed, 1);
+        assert_eq!(stats.periods.len(), 1);
+        assert_eq!(stats.periods[0].period, "2022-W44");
+        assert_eq!(stats.streaks.current, 1);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{
+    args::StatusArgs,
+    control::{self, ControlCommand, Status, TimerState},
+    error::Error,
+    Result,
+};
+use serde::Serialize;
+use std::{
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
+
+/// Interval between attempts to reach an instance, while following with none running.
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Status in waybar custom module format.
+#[derive(Debug, Serialize)]
+struct Waybar {
+    text: String,
+    tooltip: String,
+    /// Timer state (`idle` if not running).
+    class: String,
+    /// Elapsed time percentage.
+    percentage: u64,
+}
+
+/// Format `secs` as a clock (e.g. `04:59` or `1:04:59`).
+fn clock(secs: u64) -> String {
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, min, sec) => format!("{min:02}:{sec:02}"),
+        (hour, min, sec) => format!("{hour}:{min:02}:{sec:02}"),
+    }
+}
+
+/// Remaining time shown for `status` (overtime counting upward).
+fn remaining(status: &Status) -> String {
+    match status.state {
+        TimerState::Overtime => format!("+{}", clock(status.overtime)),
+        _ => clock(status.remaining),
+    }
+}
+
+/// Elapsed time percentage of `status`.
+fn progress(status: &Status) -> u64 {
+    match status.state {
+        TimerState::Running | TimerState::Paused if status.duration > 0 => {
+            (status.duration - status.remaining) * 100 / status.duration
+        }
+        TimerState::Waiting => 0,
+        _ => 100,
+    }
+}
+
+/// Name of the state of `status`.
+fn state(status: &Status) -> &'static str {
+    match status.state {
+        TimerState::Running => "running",
+        TimerState::Paused => "paused",
+        TimerState::Overtime => "overtime",
+        TimerState::Expired => "expired",
+        TimerState::Waiting => "waiting",
+    }
+}
+
+/// Render `status` according to `format` template.
+fn render(status: &Status, format: &str) -> String {
+    let activity = status
+        .activity
+        .map(|activity| activity.to_string())
+        .unwrap_or_default();
+    format
+        .replace("{activity}", &activity)
+        .replace("{remaining}", &remaining(status))
+        .replace("{progress}", &progress(status).to_string())
+        .replace("{state}", state(status))
+        .replace("{label}", status.label.as_deref().unwrap_or_default())
+        .replace("{task}", status.task.as_deref().unwrap_or_default())
+        .trim()
+        .to_string()
+}
+
+/// Render `status` (or `None` if no instance is running) as a line, according to `args`.
+fn line(status: Option<&Status>, args: &StatusArgs) -> Result<String> {
+    if !args.waybar {
+        return Ok(status.map_or(String::new(), |status| render(status, &args.format)));
+    }
+
+    let waybar = match status {
+        Some(status) => {
+            let mut tooltip = format!("{}: {} left", state(status), remaining(status));
+            if let Some(task) = &status.task {
+                tooltip += &format!("\nTask: {task}");
+            }
+            Waybar {
+                text: render(status, &args.format),
+                tooltip,
+                class: state(status).to_string(),
+                percentage: progress(status),
+            }
+        }
+        None => Waybar {
+            text: String::new(),
+            tooltip: "solanum not running".to_string(),
+            class: "idle".to_string(),
+            percentage: 0,
+        },
+    };
+
+    serde_json::to_string(&waybar).map_err(|err| Error::Other(err.to_string()))
+}
+
+/// Print `line` to standard output, failing once closed (e.g. status bar gone).
+fn print_line(line: String) -> Result<()> {
+    writeln!(io::stdout(), "{line}").map_err(|err| Error::Other(err.to_string()))
+}
+
+/// Print status of the running instance, according to `args`.
+///
+/// While no instance is running, an empty status is printed: following keeps waiting for one.
+pub fn print(args: &StatusArgs) -> Result<()> {
+    let path = control::socket_path().ok_or("unable to locate runtime directory")?;
+
+    if !args.follow {
+        let status = match control::request(&path, ControlCommand::Status) {
+            Ok(reply) => {
+                Some(serde_json::from_str(&reply).map_err(|err| Error::Other(err.to_string()))?)
+            }
+            Err(Error::Socket(..)) => None,
+            Err(err) => return Err(err),
+        };
+        return print_line(line(status.as_ref(), args)?);
+    }
+
+    let mut idle = false;
+    loop {
+        match control::follow(&path, |status| {
+            idle = false;
+            print_line(line(Some(&status), args)?)
+        }) {
+            // Session over or not running yet.
+            Ok(()) | Err(Error::Socket(..)) => {}
+            Err(err) => return Err(err),
+        }
+        if !idle {
+            idle = true;
+            print_line(line(None, args)?)?;
+        }
+        thread::sleep(RETRY_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::session::Activity;
+
+    #[test]
+    /// Status rendered with a template and as waybar JSON.
+    fn status_render() {
+        let status = Status {
+            state: TimerState::Paused,
+            activity: Some(Activity::Pomodoro(2)),
+            remaining: 5 * 60,
+            duration: 25 * 60,
+            task: Some("Write report".to_string()),
+            ..Default::default()
+        };
+        let mut args = StatusArgs {
+            format: "{activity} {remaining} {progress}% [{state}] {task}".to_string(),
+            waybar: false,
+            follow: false,
+        };
+        assert_eq!(
+            line(Some(&status), &args).unwrap(),
+            "Pomodoro #2 05:00 80% [paused] Write report"
+        );
+        assert_eq!(line(None, &args).unwrap(), "");
+
+        let overtime = Status {
+            state: TimerState::Overtime,
+            overtime: 3661,
+            ..status
+        };
+        args.format = "{remaining}".to_string();
+        assert_eq!(line(Some(&overtime), &args).unwrap(), "+1:01:01");
+
+        args.waybar = true;
+        let waybar: serde_json::Value =
+            serde_json::from_str(&line(Some(&overtime), &args).unwrap()).unwrap();
+        assert_eq!(waybar["text"], "+1:01:01");
+        assert_eq!(waybar["class"], "overtime");
+        assert_eq!(waybar["percentage"], 100);
+        let waybar: serde_json::Value = serde_json::from_str(&line(None, &args).unwrap()).unwrap();
+        assert_eq!(waybar["class"], "idle");
+    }
+}
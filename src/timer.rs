@@ -1,32 +1,95 @@
 use crate::{
-    figlet::{Figlet, Font},
+    clock::Clock,
+    duration::parse_duration,
     error::Error,
     event::Event,
-    notification::notify,
-    session::Activity,
+    figlet::{Figlet, Font},
+    notification::{notify, remind},
+    session::{Activity, ActivityRecord, Interruption, PausePolicy, SuspendPolicy},
     ui::UiCommand,
     Result,
 };
 use serde::{
     de::{self, Visitor},
-    Deserialize,
+    Deserialize, Serialize,
 };
 use std::{
     fmt::{self, Display},
     str::FromStr,
-    sync::mpsc::{Receiver, RecvError, RecvTimeoutError, Sender},
+    sync::mpsc::{Receiver, RecvTimeoutError, Sender},
     time::{Duration, Instant},
 };
 
 /// Duration of the _timer expired_ screen in seconds.
 const EXPIRED_DURATION: u64 = 5;
 
+/// Minimum gap between wall-clock and monotonic clock to be considered a system suspend.
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(10);
+
 /// [`Timer`] status.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TimerStatus {
-    Running(Activity, Timer),
-    Paused,
+    Running(Activity, #[serde(with = "wire")] Timer),
+    /// Paused [`Timer`], along with the time elapsed since paused.
+    Paused(
+        Activity,
+        #[serde(with = "wire")] Timer,
+        #[serde(with = "wire")] Timer,
+    ),
     Expired,
+    /// Expired [`Timer`] counting upward, holding the time elapsed since expiry.
+    Overtime(Activity, #[serde(with = "wire")] Timer),
+    /// Waiting for the user to start the next [`Activity`].
+    Waiting(Activity),
+}
+
+/// How a [`Timer`] run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerOutcome {
+    /// Timer expired.
+    Completed,
+    /// Timer has been skipped by the user.
+    Skipped,
+    /// Timer has been abandoned and must not be counted.
+    Voided,
+    /// Timer has been left to go back to the previous activity.
+    Back,
+    /// Application must be closed.
+    Quit,
+}
+
+impl TimerOutcome {
+    /// Name of the outcome (e.g. `skipped`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Completed => "completed",
+            Self::Skipped => "skipped",
+            Self::Voided => "voided",
+            Self::Back => "back",
+            Self::Quit => "quit",
+        }
+    }
+}
+
+/// Options affecting how a [`Timer`] runs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimerOptions {
+    /// Policy applied on system suspend.
+    pub on_suspend: SuspendPolicy,
+    /// Whether to send desktop notifications.
+    pub notifications: bool,
+    /// Time added or removed by [`Event::Extend`] and [`Event::Shorten`].
+    pub adjust_step: Duration,
+    /// Whether to keep counting upward once expired, until skipped.
+    pub overtime: bool,
+    /// Whether to show the _timer expired_ screen before the next activity starts on its own;
+    /// otherwise the caller is responsible for waiting for the user.
+    pub auto_start_next: bool,
+    /// Pause duration after which [`TimerOptions::on_max_pause`] is applied.
+    pub max_pause: Option<Duration>,
+    /// Policy applied once paused for longer than [`TimerOptions::max_pause`].
+    pub on_max_pause: PausePolicy,
 }
 
 /// [`Timer`] data for [`Ui`](crate::ui::Ui) rendering.
@@ -42,14 +105,17 @@ pub struct TimerData {
     pub figlet: String,
     /// Timer remaining percentage.
     pub perc: u16,
+    /// Whether the timer is in overtime.
+    pub overtime: bool,
 }
 
 impl TimerData {
-    pub fn new(activity: Activity, figlet: String, perc: f32) -> Self {
+    pub fn new(activity: Activity, figlet: String, perc: f32, overtime: bool) -> Self {
         Self {
             activity,
             figlet,
             perc: perc as u16,
+            overtime,
         }
     }
 }
@@ -60,6 +126,7 @@ impl Default for TimerData {
             activity: Activity::Pomodoro(0),
             figlet: String::default(),
             perc: 100,
+            overtime: false,
         }
     }
 }
@@ -71,6 +138,8 @@ pub struct Timer {
     total: usize,
     /// Residue duration of the [`Timer`].
     residue: usize,
+    /// Time already elapsed when the [`Timer`] was resumed, which a previous run accounted for.
+    resumed: usize,
 }
 
 impl Timer {
@@ -82,6 +151,7 @@ impl Timer {
         Self {
             total: duration,
             residue: duration,
+            resumed: 0,
         }
     }
 
@@ -102,6 +172,21 @@ impl Timer {
         format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
     }
 
+    /// Convert [`Timer`] to FIGlet text as overtime (e.g. "+00:03:12").
+    pub fn overtime_figlet(&self, font: &Font) -> String {
+        font.convert(&format!("+{}", self.hhmmss()))
+    }
+
+    /// Return the total duration of the [`Timer`].
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.total as u64)
+    }
+
+    /// Return the remaining duration of the [`Timer`].
+    pub fn remaining(&self) -> Duration {
+        Duration::from_secs(self.residue as u64)
+    }
+
     /// Return the remaining percentage of the [`Timer`].
     pub fn remaining_percentage(&self) -> f32 {
         (self.residue as f32 / self.total as f32) * 100.0
@@ -109,66 +194,355 @@ impl Timer {
 
     /// Start [`Timer`].
     ///
-    /// Return value of `true` indicates to the caller that application must be closed.
-    pub fn start(
+    /// The countdown is driven by a monotonic deadline: remaining time is always computed from
+    /// the clock, so events, pauses and slow renders never make the [`Timer`] drift. Since the
+    /// monotonic clock stops while the system is suspended, the wall-clock is used to detect
+    /// suspend gaps, which are handled according to [`TimerOptions::on_suspend`].
+    ///
+    /// The running [`Timer`] can be extended or shortened by [`TimerOptions::adjust_step`]: the
+    /// [`ActivityRecord`] returned reports the adjustment and the actual duration of the run.
+    ///
+    /// With [`TimerOptions::overtime`], the expired [`Timer`] keeps counting upward until skipped,
+    /// instead of showing the _timer expired_ screen.
+    ///
+    /// The running [`Timer`] can also be restarted, voided to abandon it, or left to go back to
+    /// the previous activity.
+    ///
+    /// Every [`TimerStatus`] drawn while running or paused is passed to `checkpoint` too, e.g. to
+    /// save it: that's best effort, never ending the run.
+    ///
+    /// The run is recorded into `record`, prepared by the caller for the activity to start.
+    pub fn start<C: Clock>(
         &mut self,
-        activity: Activity,
+        mut record: ActivityRecord,
+        options: TimerOptions,
+        clock: &C,
         tx_ui: &Sender<UiCommand>,
         rx_event: &Receiver<Event>,
-    ) -> Result<bool> {
-        let delay = |time: Instant| -> Result<Duration> {
-            Duration::from_millis(999)
-                .checked_sub(time.elapsed())
-                .ok_or(Error::RenderTime)
-        };
+        checkpoint: &mut impl FnMut(&TimerStatus),
+    ) -> Result<ActivityRecord> {
+        let activity = record.activity;
+        let step = ceil_secs(options.adjust_step);
+        tx_ui
+            .send(UiCommand::Interruptions(record.interruptions))
+            .unwrap();
+        // Instant at which the timer expires.
+        let mut deadline = deadline_after(clock.now(), Duration::from_secs(self.residue as u64))?;
+
+        // Countdown loop, evaluating to its outcome and the remaining time once over.
+        let (outcome, remaining) = loop {
+            let remaining = deadline.saturating_duration_since(clock.now());
+            self.residue = ceil_secs(remaining);
+            if self.residue == 0 {
+                break (TimerOutcome::Completed, remaining);
+            }
 
-        // Countdown loop.
-        while self.residue > 0 {
-            let start = Instant::now();
-            tx_ui
-                .send(UiCommand::Draw(TimerStatus::Running(activity, *self)))
-                .unwrap();
+            let status = TimerStatus::Running(activity, *self);
+            checkpoint(&status);
+            tx_ui.send(UiCommand::Draw(status)).unwrap();
 
-            // Let 1 second pass while still being responsive to events.
+            // Wait until the displayed second changes while still being responsive to events.
             // Receiving `RecvTimeoutError::Timeout` means the delay reached Timeout with no
             // events.
-            match rx_event.recv_timeout(delay(start)?) {
+            let tick = remaining - Duration::from_secs(self.residue as u64 - 1);
+            let (tick_start, tick_start_wall) = (clock.now(), clock.wall());
+            let mut pause = false;
+            match clock.recv_timeout(rx_event, tick) {
                 Err(RecvTimeoutError::Timeout) => {}
-                Ok(Event::TogglePause) => {
-                    // Send Pause screen to Ui and wait until next event.
-                    tx_ui.send(UiCommand::Draw(TimerStatus::Paused)).unwrap();
-                    match rx_event.recv() {
-                        Ok(Event::TogglePause) => continue,
-                        Ok(Event::Skip) => break,
-                        Err(RecvError) => return Ok(true),
-                    }
+                Ok(Event::TogglePause | Event::Pause) => pause = true,
+                // Detaching is up to attached clients.
+                Ok(Event::Start | Event::Resume | Event::Detach) => {}
+                Ok(Event::SetTask(task)) => set_task(&mut record, task, tx_ui),
+                Ok(Event::Interruption(interruption)) => {
+                    interrupt(&mut record, interruption, tx_ui)
+                }
+                Ok(Event::Skip) => {
+                    let remaining = deadline.saturating_duration_since(clock.now());
+                    break (TimerOutcome::Skipped, remaining);
+                }
+                Ok(Event::Void) => {
+                    let remaining = deadline.saturating_duration_since(clock.now());
+                    break (TimerOutcome::Voided, remaining);
+                }
+                Ok(Event::Back) => {
+                    let remaining = deadline.saturating_duration_since(clock.now());
+                    break (TimerOutcome::Back, remaining);
+                }
+                Ok(Event::Restart) => {
+                    let remaining = deadline.saturating_duration_since(clock.now());
+                    self.restart(&mut record, remaining);
+                    deadline = deadline_after(clock.now(), self.duration())?;
+                }
+                Ok(Event::Extend) => {
+                    deadline = deadline_after(deadline, Duration::from_secs(step as u64))?;
+                    self.total = self.total.checked_add(step).ok_or(Error::TimerOverflow)?;
+                    record.adjustment += step as i64;
+                }
+                Ok(Event::Shorten) => {
+                    // Never cut more than the remaining time.
+                    let cut = step.min(ceil_secs(deadline.saturating_duration_since(clock.now())));
+                    deadline = deadline
+                        .checked_sub(Duration::from_secs(cut as u64))
+                        .unwrap_or_else(|| clock.now());
+                    self.total = self.total.saturating_sub(cut);
+                    record.adjustment -= cut as i64;
                 }
-                Ok(Event::Skip) => break,
                 // EventHandler disconnected, cose application.
-                Err(RecvTimeoutError::Disconnected) => return Ok(true),
+                Ok(Event::Quit) | Err(RecvTimeoutError::Disconnected) => {
+                    let remaining = deadline.saturating_duration_since(clock.now());
+                    return Ok(self.conclude(record, TimerOutcome::Quit, remaining));
+                }
             };
 
-            self.residue -= 1;
+            // Detect system suspend: wall-clock time elapsed while the monotonic clock stood
+            // still.
+            let gap = clock
+                .wall()
+                .duration_since(tick_start_wall)
+                .unwrap_or_default()
+                .saturating_sub(clock.now() - tick_start);
+            if gap >= SUSPEND_THRESHOLD {
+                let slept = Timer::new(0, 0, gap.as_secs() as usize);
+                let notice = match options.on_suspend {
+                    SuspendPolicy::Count => {
+                        deadline = deadline.checked_sub(gap).unwrap_or_else(|| clock.now());
+                        format!("System suspended for {slept}: time counted")
+                    }
+                    SuspendPolicy::Pause => {
+                        pause = true;
+                        format!("System suspended for {slept}: timer paused")
+                    }
+                    SuspendPolicy::Void => {
+                        tx_ui
+                            .send(UiCommand::Notice(format!(
+                                "System suspended for {slept}: {activity} voided"
+                            )))
+                            .unwrap();
+                        let remaining = deadline.saturating_duration_since(clock.now());
+                        break (TimerOutcome::Voided, remaining);
+                    }
+                };
+                tx_ui.send(UiCommand::Notice(notice)).unwrap();
+            }
+
+            if pause {
+                // Freeze remaining time, send Pause screen to Ui and wait until resumed, keeping
+                // track of the time spent paused.
+                let mut remaining = deadline.saturating_duration_since(clock.now());
+                let paused_at = clock.now();
+                let mut reminded = false;
+                let outcome = loop {
+                    let paused = clock.now() - paused_at;
+                    let paused_timer = Timer::new(0, 0, paused.as_secs() as usize);
+
+                    if let Some(max_pause) = options.max_pause.filter(|max| paused >= *max) {
+                        let max_pause = Timer::new(0, 0, ceil_secs(max_pause));
+                        match options.on_max_pause {
+                            PausePolicy::Void => {
+                                tx_ui
+                                    .send(UiCommand::Notice(format!(
+                                        "Paused for more than {max_pause}: {activity} voided"
+                                    )))
+                                    .unwrap();
+                                break Some(TimerOutcome::Voided);
+                            }
+                            // Remind only once per pause.
+                            PausePolicy::Remind if !reminded => {
+                                reminded = true;
+                                tx_ui
+                                    .send(UiCommand::Notice(format!(
+                                        "Paused for more than {max_pause}"
+                                    )))
+                                    .unwrap();
+                                if options.notifications {
+                                    remind(activity, max_pause)?;
+                                }
+                            }
+                            PausePolicy::Remind => {}
+                        }
+                    }
+
+                    let status = TimerStatus::Paused(activity, *self, paused_timer);
+                    checkpoint(&status);
+                    tx_ui.send(UiCommand::Draw(status)).unwrap();
+
+                    // Wait until the displayed pause duration changes.
+                    let tick = Duration::from_secs(paused.as_secs() + 1) - paused;
+                    match clock.recv_timeout(rx_event, tick) {
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Ok(Event::TogglePause | Event::Resume) => break None,
+                        Ok(Event::Skip) => break Some(TimerOutcome::Skipped),
+                        Ok(Event::Void) => break Some(TimerOutcome::Voided),
+                        Ok(Event::Back) => break Some(TimerOutcome::Back),
+                        // Restarted timer stays paused.
+                        Ok(Event::Restart) => {
+                            self.restart(&mut record, remaining);
+                            remaining = self.duration();
+                        }
+                        Ok(Event::SetTask(task)) => set_task(&mut record, task, tx_ui),
+                        Ok(Event::Interruption(interruption)) => {
+                            interrupt(&mut record, interruption, tx_ui)
+                        }
+                        // Timer can't be adjusted while paused.
+                        Ok(Event::Extend | Event::Shorten | Event::Start | Event::Pause) => {}
+                        Ok(Event::Detach) => {}
+                        Ok(Event::Quit) | Err(RecvTimeoutError::Disconnected) => {
+                            break Some(TimerOutcome::Quit)
+                        }
+                    }
+                };
+                record.paused += clock.now() - paused_at;
+
+                match outcome {
+                    None => deadline = deadline_after(clock.now(), remaining)?,
+                    Some(TimerOutcome::Quit) => {
+                        return Ok(self.conclude(record, TimerOutcome::Quit, remaining))
+                    }
+                    Some(outcome) => break (outcome, remaining),
+                }
+            }
+        };
+
+        // Going back is not worth a notification.
+        if outcome == TimerOutcome::Back {
+            return Ok(self.conclude(record, outcome, remaining));
         }
 
         // Send desktop notification.
-        notify(activity)?;
+        if options.notifications {
+            notify(activity, outcome, record.task.as_deref())?;
+        }
+
+        // Voided timers end right away.
+        if outcome == TimerOutcome::Voided {
+            return Ok(self.conclude(record, outcome, remaining));
+        }
+
+        // Count upward until the user moves on (only if completed).
+        if options.overtime && outcome == TimerOutcome::Completed {
+            let expired = clock.now();
+            loop {
+                let overtime = clock.now() - expired;
+                record.overtime = overtime;
+                let timer = Timer::new(0, 0, overtime.as_secs() as usize);
+                tx_ui
+                    .send(UiCommand::Draw(TimerStatus::Overtime(activity, timer)))
+                    .unwrap();
+
+                // Wait until the displayed second changes.
+                let tick = Duration::from_secs(overtime.as_secs() + 1) - overtime;
+                match clock.recv_timeout(rx_event, tick) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Ok(Event::Skip | Event::Start) => {
+                        record.overtime = clock.now() - expired;
+                        return Ok(self.conclude(record, outcome, remaining));
+                    }
+                    Ok(Event::SetTask(task)) => set_task(&mut record, task, tx_ui),
+                    // Pomodoro is over all the same.
+                    Ok(Event::Quit) | Err(RecvTimeoutError::Disconnected) => {
+                        record.overtime = clock.now() - expired;
+                        record.quit = true;
+                        return Ok(self.conclude(record, outcome, remaining));
+                    }
+                    Ok(_) => {}
+                }
+            }
+        }
+
+        // The caller will wait for the user to start the next activity.
+        if !options.auto_start_next {
+            return Ok(self.conclude(record, outcome, remaining));
+        }
+
         // Send Expired screen to Ui, meanwhile listen for events.
         tx_ui.send(UiCommand::Draw(TimerStatus::Expired)).unwrap();
-        let start = Instant::now();
-        while start.elapsed() <= Duration::from_secs(EXPIRED_DURATION) {
-            match rx_event.recv_timeout(Duration::from_secs(EXPIRED_DURATION)) {
+        let deadline = deadline_after(clock.now(), Duration::from_secs(EXPIRED_DURATION))?;
+        loop {
+            match clock.recv_timeout(rx_event, deadline.saturating_duration_since(clock.now())) {
                 Err(RecvTimeoutError::Timeout) => break,
-                Err(RecvTimeoutError::Disconnected) => return Ok(true),
+                // Timer is over all the same.
+                Ok(Event::Quit) | Err(RecvTimeoutError::Disconnected) => {
+                    record.quit = true;
+                    break;
+                }
                 Ok(_) => {}
             }
         }
 
-        // Reset `residue`.
+        Ok(self.conclude(record, outcome, remaining))
+    }
+
+    /// Restart [`Timer`] with `remaining` time left: time already spent still counts in
+    /// `record`.
+    fn restart(&mut self, record: &mut ActivityRecord, remaining: Duration) {
+        record.actual += self.elapsed(remaining);
+        self.residue = self.total;
+        self.resumed = 0;
+    }
+
+    /// Complete `record` of a run ended with `outcome` and `remaining` time left, then reset the
+    /// [`Timer`] to its planned duration for the next run.
+    fn conclude(
+        &mut self,
+        mut record: ActivityRecord,
+        outcome: TimerOutcome,
+        remaining: Duration,
+    ) -> ActivityRecord {
+        record.outcome = outcome;
+        record.actual += self.elapsed(remaining);
+        record.remaining = remaining;
+
+        self.total = record.planned.as_secs() as usize;
         self.residue = self.total;
+        self.resumed = 0;
 
-        Ok(false)
+        record
     }
+
+    /// Time elapsed in this run with `remaining` time left.
+    fn elapsed(&self, remaining: Duration) -> Duration {
+        self.duration()
+            .saturating_sub(remaining)
+            .saturating_sub(Duration::from_secs(self.resumed as u64))
+    }
+
+    /// Resume [`Timer`] from `remaining` time left by a previous run, e.g. when going back to it.
+    ///
+    /// A [`Timer`] with no time left starts over from its full duration.
+    pub fn resume(&mut self, remaining: Duration) {
+        self.residue = match ceil_secs(remaining).min(self.total) {
+            0 => self.total,
+            residue => residue,
+        };
+        self.resumed = self.total - self.residue;
+    }
+}
+
+/// Set the task of the activity being recorded into `record`, showing it in the Ui.
+fn set_task(record: &mut ActivityRecord, task: Option<String>, tx_ui: &Sender<UiCommand>) {
+    record.task = task;
+    tx_ui.send(UiCommand::Task(record.task.clone())).unwrap();
+}
+
+/// Count `interruption` into `record`, if running a pomodoro, showing it in the Ui.
+fn interrupt(record: &mut ActivityRecord, interruption: Interruption, tx_ui: &Sender<UiCommand>) {
+    if let Activity::Pomodoro(_) = record.activity {
+        record.interruptions.add(interruption);
+        tx_ui
+            .send(UiCommand::Interruptions(record.interruptions))
+            .unwrap();
+    }
+}
+
+/// Return the [`Instant`] at which `duration` will have elapsed from `now`.
+fn deadline_after(now: Instant, duration: Duration) -> Result<Instant> {
+    now.checked_add(duration).ok_or(Error::TimerOverflow)
+}
+
+/// Round [`Duration`] up to whole seconds, so that the [`Timer`] displays `00:00:00` only once
+/// expired.
+fn ceil_secs(duration: Duration) -> usize {
+    (duration.as_secs() + u64::from(duration.subsec_nanos() > 0)) as usize
 }
 
 impl Display for Timer {
@@ -182,7 +556,7 @@ impl Display for Timer {
         if minutes > 0 {
             write!(f, "{}m", minutes)?;
         }
-        if seconds > 0 {
+        if seconds > 0 || self.residue == 0 {
             write!(f, "{}s", seconds)?;
         }
 
@@ -192,53 +566,16 @@ impl Display for Timer {
 
 impl FromStr for Timer {
     type Err = crate::error::Error;
-    /// Parse [`Timer`] from string (e.g. "1h2m30s").
+    /// Parse [`Timer`] from string (e.g. "1h2m30s", "25", "25:00", "1h 30m", "PT25M").
+    ///
+    /// See [`parse_duration`] for the accepted formats.
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        if !s.ends_with(['h', 'H', 'm', 'M', 's', 'S']) {
-            return Err(Self::Err::ParseTimer(format!(
-                "expected `[[<H>h]<M>m]<S>s` format (found `{}`)",
-                s
-            )));
-        }
-
-        let mut chars: Vec<char> = vec![];
-        let mut duration: usize = 0;
-
-        for c in s.chars() {
-            match c {
-                'h' | 'H' | 'm' | 'M' | 's' | 'S' => {
-                    if chars.is_empty() {
-                        return Err(Self::Err::ParseTimer(format!("null {} value", c)));
-                    }
-
-                    let v: String = chars.iter().collect(); // Concatenate chars into one string.
-                    chars.clear(); // Clear the chars vector for later reuse.
-
-                    // Safe to unwrap since chars are filtered for digits.
-                    let mut value = v.parse::<usize>().unwrap();
-
-                    match c {
-                        'h' | 'H' => value *= 3600,
-                        'm' | 'M' => value *= 60,
-                        _ => {}
-                    }
-
-                    duration = duration
-                        .checked_add(value)
-                        .ok_or(Self::Err::TimerOverflow)?;
-                }
-                c => {
-                    if !c.is_ascii_digit() {
-                        return Err(Self::Err::ParseTimer(format!("`{}` is not a digit", c)));
-                    }
-                    chars.push(c);
-                }
-            }
-        }
+        let duration = parse_duration(s)?;
 
         Ok(Self {
             total: duration,
             residue: duration,
+            resumed: 0,
         })
     }
 }
@@ -256,7 +593,7 @@ impl<'de> Visitor<'de> for TimerVisitor {
     type Value = Timer;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("`[[<H>h]<M>m]<S>s` format")
+        formatter.write_str("duration (e.g. `25`, `25m`, `25:00`, `1h 30m`, `PT25M`)")
     }
 
     fn visit_str<E>(self, v: &str) -> std::result::Result<Self::Value, E>
@@ -265,6 +602,22 @@ impl<'de> Visitor<'de> for TimerVisitor {
     {
         Timer::from_str(v).map_err(de::Error::custom)
     }
+
+    /// Bare integers are minutes, as in string durations.
+    fn visit_i64<E>(self, v: i64) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+
+    /// Bare floats are minutes, as in string durations.
+    fn visit_f64<E>(self, v: f64) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
 }
 
 impl<'de> Deserialize<'de> for Timer {
@@ -272,13 +625,95 @@ impl<'de> Deserialize<'de> for Timer {
     where
         D: serde::Deserializer<'de>,
     {
-        deserializer.deserialize_str(TimerVisitor)
+        deserializer.deserialize_any(TimerVisitor)
+    }
+}
+
+/// Running [`Timer`]s of a [`TimerStatus`] serialized as a whole (e.g. to be sent to attached
+/// clients), while configuration only accepts durations.
+mod wire {
+    use super::Timer;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct WireTimer {
+        total: usize,
+        residue: usize,
+        resumed: usize,
+    }
+
+    pub fn serialize<S>(timer: &Timer, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        WireTimer {
+            total: timer.total,
+            residue: timer.residue,
+            resumed: timer.resumed,
+        }
+        .serialize(serializer)
+    }
+
+    /// Times received are kept within the total duration.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Timer, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let timer = WireTimer::deserialize(deserializer)?;
+        Ok(Timer {
+            total: timer.total,
+            residue: timer.residue.min(timer.total),
+            resumed: timer.resumed.min(timer.total),
+        })
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{clock::ManualClock, session::Interruptions};
+    use std::sync::mpsc;
+
+    /// Options with no desktop notifications.
+    fn options(on_suspend: SuspendPolicy) -> TimerOptions {
+        TimerOptions {
+            on_suspend,
+            notifications: false,
+            adjust_step: Duration::from_secs(5 * 60),
+            overtime: false,
+            auto_start_next: true,
+            max_pause: None,
+            on_max_pause: PausePolicy::Remind,
+        }
+    }
+
+    /// Run `timer` on `clock`, sending `events` at the given minutes.
+    fn run(
+        timer: &mut Timer,
+        options: TimerOptions,
+        events: impl IntoIterator<Item = (u64, Event)>,
+        clock: &ManualClock,
+    ) -> ActivityRecord {
+        let (tx_ui, _rx_ui) = mpsc::channel();
+        let (tx_event, rx_event) = mpsc::channel();
+        for (minute, event) in events {
+            let tx_event = tx_event.clone();
+            clock.schedule(Duration::from_secs(minute * 60), move || {
+                tx_event.send(event).unwrap()
+            });
+        }
+
+        timer
+            .start(
+                ActivityRecord::new(Activity::Pomodoro(1), timer.duration()),
+                options,
+                clock,
+                &tx_ui,
+                &rx_event,
+                &mut |_| {},
+            )
+            .unwrap()
+    }
 
     #[test]
     /// Timer displaying as `_h_m_s` format.
@@ -287,6 +722,7 @@ mod test {
         assert_eq!(Timer::new(0, 2, 122).to_string(), "4m2s");
         assert_eq!(Timer::new(1, 0, 10).to_string(), "1h10s");
         assert_eq!(Timer::new(0, 5, 0).to_string(), "5m");
+        assert_eq!(Timer::new(0, 0, 0).to_string(), "0s");
     }
 
     #[test]
@@ -296,5 +732,258 @@ mod test {
         assert_eq!(Timer::from_str("4m2s").unwrap().to_string(), "4m2s");
         assert_eq!(Timer::from_str("1h10s").unwrap().to_string(), "1h10s");
         assert_eq!(Timer::from_str("5m").unwrap().to_string(), "5m");
+        assert_eq!(Timer::from_str("25").unwrap().to_string(), "25m");
+        assert_eq!(Timer::from_str("1:30:00").unwrap().to_string(), "1h30m");
+        assert_eq!(Timer::from_str("PT1H30M").unwrap().to_string(), "1h30m");
+    }
+
+    #[test]
+    /// Timer deserializing from TOML strings and numbers.
+    fn timer_deserialize() {
+        #[derive(Deserialize)]
+        struct Timers {
+            a: Timer,
+            b: Timer,
+            c: Timer,
+        }
+
+        let timers: Timers = toml::from_str("a = \"1h 30m\"\nb = 25\nc = 1.5").unwrap();
+        assert_eq!(timers.a.to_string(), "1h30m");
+        assert_eq!(timers.b.to_string(), "25m");
+        assert_eq!(timers.c.to_string(), "1m30s");
+        // Running timers are no configuration.
+        assert!(toml::from_str::<Timers>("a = { total = 0, residue = 60 }\nb = 1\nc = 1").is_err());
+    }
+
+    #[test]
+    /// Remaining time rounding up to whole seconds.
+    fn timer_ceil_secs() {
+        assert_eq!(ceil_secs(Duration::ZERO), 0);
+        assert_eq!(ceil_secs(Duration::from_millis(1)), 1);
+        assert_eq!(ceil_secs(Duration::from_millis(1000)), 1);
+        assert_eq!(ceil_secs(Duration::from_millis(1500)), 2);
+        assert_eq!(ceil_secs(Duration::from_secs(25 * 60)), 1500);
+    }
+
+    #[test]
+    /// Timer expiring exactly after its duration, followed by the expired screen.
+    fn timer_expires_on_time() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::new(0, 25, 0);
+
+        let record = run(&mut timer, options(SuspendPolicy::Count), [], &clock);
+        assert_eq!(record.outcome, TimerOutcome::Completed);
+        assert_eq!(record.actual, Duration::from_secs(25 * 60));
+        assert_eq!(
+            clock.elapsed(),
+            Duration::from_secs(25 * 60 + EXPIRED_DURATION)
+        );
+        assert_eq!(timer.residue, timer.total);
+    }
+
+    #[test]
+    /// Paused time not counting toward the timer.
+    fn timer_pause() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::new(0, 25, 0);
+        let events = [(10, Event::TogglePause), (15, Event::TogglePause)];
+
+        let record = run(&mut timer, options(SuspendPolicy::Count), events, &clock);
+        assert_eq!(record.actual, Duration::from_secs(25 * 60));
+        assert_eq!(record.paused, Duration::from_secs(5 * 60));
+        assert_eq!(
+            clock.elapsed(),
+            Duration::from_secs(30 * 60 + EXPIRED_DURATION)
+        );
+    }
+
+    #[test]
+    /// Timer paused for longer than `max_pause`.
+    fn timer_max_pause() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::new(0, 25, 0);
+        let options = TimerOptions {
+            max_pause: Some(Duration::from_secs(3 * 60)),
+            on_max_pause: PausePolicy::Void,
+            ..options(SuspendPolicy::Count)
+        };
+
+        let record = run(&mut timer, options, [(10, Event::TogglePause)], &clock);
+        assert_eq!(record.outcome, TimerOutcome::Voided);
+        assert_eq!(record.actual, Duration::from_secs(10 * 60));
+        assert_eq!(record.paused, Duration::from_secs(3 * 60));
+        assert_eq!(clock.elapsed(), Duration::from_secs(13 * 60));
+
+        // Reminded only: timer stays paused until resumed.
+        let clock = ManualClock::new();
+        let options = TimerOptions {
+            on_max_pause: PausePolicy::Remind,
+            ..options
+        };
+        let events = [(10, Event::TogglePause), (20, Event::TogglePause)];
+        let record = run(&mut timer, options, events, &clock);
+        assert_eq!(record.outcome, TimerOutcome::Completed);
+        assert_eq!(record.paused, Duration::from_secs(10 * 60));
+    }
+
+    #[test]
+    /// Running timer extended and shortened.
+    fn timer_adjust() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::new(0, 25, 0);
+        let events = [
+            (10, Event::Extend),
+            (11, Event::Extend),
+            (12, Event::Shorten),
+        ];
+
+        let record = run(&mut timer, options(SuspendPolicy::Count), events, &clock);
+        assert_eq!(record.adjustment, 5 * 60);
+        assert_eq!(record.actual, Duration::from_secs(30 * 60));
+        assert_eq!(
+            clock.elapsed(),
+            Duration::from_secs(30 * 60 + EXPIRED_DURATION)
+        );
+        // Planned duration restored for the next run.
+        assert_eq!(timer.total, 25 * 60);
+
+        // Shortening never cuts more than the remaining time.
+        let clock = ManualClock::new();
+        let events = [(23, Event::Shorten)];
+        let record = run(&mut timer, options(SuspendPolicy::Count), events, &clock);
+        assert_eq!(record.adjustment, -2 * 60);
+        assert_eq!(record.actual, Duration::from_secs(23 * 60));
+    }
+
+    #[test]
+    /// Running timer restarted and voided.
+    fn timer_restart_void() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::new(0, 25, 0);
+        let options = options(SuspendPolicy::Count);
+
+        let record = run(&mut timer, options, [(10, Event::Restart)], &clock);
+        assert_eq!(record.outcome, TimerOutcome::Completed);
+        assert_eq!(record.actual, Duration::from_secs(35 * 60));
+        assert_eq!(
+            clock.elapsed(),
+            Duration::from_secs(35 * 60 + EXPIRED_DURATION)
+        );
+
+        // Voided timers end right away, with no expired screen.
+        let clock = ManualClock::new();
+        let record = run(&mut timer, options, [(10, Event::Void)], &clock);
+        assert_eq!(record.outcome, TimerOutcome::Voided);
+        assert_eq!(record.actual, Duration::from_secs(10 * 60));
+        assert_eq!(clock.elapsed(), Duration::from_secs(10 * 60));
+        assert_eq!(timer.residue, timer.total);
+    }
+
+    #[test]
+    /// Interruptions counted while running or paused.
+    fn timer_interruptions() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::new(0, 25, 0);
+        let events = [
+            (5, Event::Interruption(Interruption::Internal)),
+            (10, Event::TogglePause),
+            (12, Event::Interruption(Interruption::External)),
+            (15, Event::TogglePause),
+            (20, Event::Interruption(Interruption::Internal)),
+        ];
+        let record = run(&mut timer, options(SuspendPolicy::Count), events, &clock);
+        assert_eq!(record.outcome, TimerOutcome::Completed);
+        assert_eq!(
+            record.interruptions,
+            Interruptions {
+                internal: 2,
+                external: 1
+            }
+        );
+        assert_eq!(record.interruptions.to_string(), "''~");
+    }
+
+    #[test]
+    /// Timer left to go back, then resumed from its remaining time.
+    fn timer_back_resume() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::new(0, 25, 0);
+        let options = options(SuspendPolicy::Count);
+
+        let record = run(&mut timer, options, [(10, Event::Back)], &clock);
+        assert_eq!(record.outcome, TimerOutcome::Back);
+        assert_eq!(record.actual, Duration::from_secs(10 * 60));
+        assert_eq!(record.remaining, Duration::from_secs(15 * 60));
+        assert_eq!(clock.elapsed(), Duration::from_secs(10 * 60));
+
+        // Time spent in the previous run is not counted again.
+        let clock = ManualClock::new();
+        timer.resume(record.remaining);
+        assert_eq!(timer.residue, 15 * 60);
+        let record = run(&mut timer, options, [], &clock);
+        assert_eq!(record.outcome, TimerOutcome::Completed);
+        assert_eq!(record.actual, Duration::from_secs(15 * 60));
+        assert_eq!(timer.residue, timer.total);
+
+        // Expired timers start over.
+        timer.resume(Duration::ZERO);
+        assert_eq!(timer.residue, timer.total);
+    }
+
+    #[test]
+    /// Expired timer counting upward until skipped.
+    fn timer_overtime() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::new(0, 25, 0);
+        let options = TimerOptions {
+            overtime: true,
+            ..options(SuspendPolicy::Count)
+        };
+
+        let record = run(&mut timer, options, [(30, Event::Skip)], &clock);
+        assert_eq!(record.outcome, TimerOutcome::Completed);
+        assert_eq!(record.actual, Duration::from_secs(25 * 60));
+        assert_eq!(record.overtime, Duration::from_secs(5 * 60));
+        assert_eq!(clock.elapsed(), Duration::from_secs(30 * 60));
+
+        // Skipped timers don't go in overtime.
+        let clock = ManualClock::new();
+        let record = run(&mut timer, options, [(10, Event::Skip)], &clock);
+        assert_eq!(record.overtime, Duration::ZERO);
+        assert_eq!(
+            clock.elapsed(),
+            Duration::from_secs(10 * 60 + EXPIRED_DURATION)
+        );
+    }
+
+    #[test]
+    /// System suspend handled according to [`SuspendPolicy`].
+    fn timer_suspend() {
+        let suspend = |clock: &ManualClock| {
+            let clock_ = clock.clone();
+            clock.schedule(Duration::from_secs(5 * 60), move || {
+                clock_.suspend(Duration::from_secs(10 * 60))
+            });
+        };
+
+        // Time slept counts toward the timer.
+        let clock = ManualClock::new();
+        suspend(&clock);
+        let mut timer = Timer::new(0, 25, 0);
+        let record = run(&mut timer, options(SuspendPolicy::Count), [], &clock);
+        assert_eq!(record.outcome, TimerOutcome::Completed);
+        assert_eq!(
+            clock.elapsed(),
+            Duration::from_secs(15 * 60 + EXPIRED_DURATION)
+        );
+
+        // Timer voided right after resume.
+        let clock = ManualClock::new();
+        suspend(&clock);
+        let mut timer = Timer::new(0, 25, 0);
+        let record = run(&mut timer, options(SuspendPolicy::Void), [], &clock);
+        assert_eq!(record.outcome, TimerOutcome::Voided);
+        assert_eq!(clock.elapsed(), Duration::from_secs(5 * 60));
+        assert_eq!(timer.residue, timer.total);
     }
 }
@@ -1,17 +1,23 @@
 use crate::{
     error::Error,
     figlet::{Figlet, Font},
-    session::Activity,
-    timer::{TimerData, TimerStatus},
+    session::{Activity, Interruptions},
+    timer::{Timer, TimerData, TimerStatus},
     Result,
 };
+use chrono::Local;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use serde::Deserialize;
-use std::{io, result, sync::mpsc::Receiver, thread};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, Write},
+    result,
+    sync::mpsc::Receiver,
+    thread,
+};
 use tui::{
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout},
@@ -47,7 +53,7 @@ pub fn restore_terminal(
     Ok(())
 }
 
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 /// UI colors.
 #[serde(rename_all = "lowercase")]
 pub enum Color {
@@ -91,9 +97,24 @@ impl From<Color> for style::Color {
 }
 
 /// User interface commands.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum UiCommand {
     Draw(TimerStatus),
+    /// Show a notice to the user until the current activity changes.
+    Notice(String),
+    /// Progress toward the daily goal.
+    Goal(String),
+    /// Label and color of the sequence step about to start.
+    Step {
+        label: Option<String>,
+        color: Option<Color>,
+    },
+    /// Task being worked on.
+    Task(Option<String>),
+    /// Task being typed by the user, if any.
+    Input(Option<String>),
+    /// Interruptions counted during the running activity.
+    Interruptions(Interruptions),
     Refresh,
 }
 
@@ -102,8 +123,11 @@ pub enum UiCommand {
 pub enum Screen {
     #[default]
     Running,
-    Paused,
+    /// Paused, holding the time elapsed since paused.
+    Paused(Timer),
     Expired,
+    /// Expired, waiting for the user to start next [`Activity`].
+    Waiting(Activity),
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -121,6 +145,9 @@ pub struct UiOptions {
     /// Progress bar background.
     #[serde(default = "default_background_color")]
     background_color: Color,
+    /// Progress bar color in overtime.
+    #[serde(default = "default_overtime_color")]
+    overtime_color: Color,
     /// FIGlet font.
     #[serde(default)]
     pub font: Font,
@@ -146,6 +173,11 @@ fn default_background_color() -> Color {
     Color::DarkGray
 }
 
+#[inline]
+fn default_overtime_color() -> Color {
+    Color::Yellow
+}
+
 impl Default for UiOptions {
     fn default() -> Self {
         Self {
@@ -153,6 +185,7 @@ impl Default for UiOptions {
             short_break_color: default_short_break_color(),
             long_break_color: default_long_break_color(),
             background_color: default_background_color(),
+            overtime_color: default_overtime_color(),
             font: Default::default(),
         }
     }
@@ -167,6 +200,20 @@ pub struct Ui {
     timer_data: TimerData,
     /// Current screen.
     screen: Screen,
+    /// Notice for the user (e.g. system suspend handling).
+    notice: Option<String>,
+    /// Progress toward the daily goal.
+    goal: Option<String>,
+    /// Label of the current sequence step.
+    label: Option<String>,
+    /// Color of the current sequence step, overriding the one of the activity.
+    color: Option<Color>,
+    /// Task being worked on.
+    task: Option<String>,
+    /// Task being typed by the user, if any.
+    input: Option<String>,
+    /// Interruptions counted during the running activity.
+    interruptions: Interruptions,
 }
 
 impl Ui {
@@ -176,18 +223,32 @@ impl Ui {
             options,
             timer_data: Default::default(),
             screen: Default::default(),
+            notice: None,
+            goal: None,
+            label: None,
+            color: None,
+            task: None,
+            input: None,
+            interruptions: Interruptions::default(),
         }
     }
 
+    /// Prompt for the task being typed, if any.
+    fn prompt(&self) -> Option<String> {
+        self.input
+            .as_ref()
+            .map(|input| format!("Task: {input}_  [Enter] set  [Esc] cancel"))
+    }
+
     /// Render timer screen (whether running or paused).
     fn render_timer(&self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
         terminal
             .draw(|frame| {
-                let color = match self.timer_data.activity {
+                let color = self.color.unwrap_or(match self.timer_data.activity {
                     Activity::Pomodoro(_) => self.options.pomodoro_color,
                     Activity::ShortBreak => self.options.short_break_color,
                     Activity::LongBreak => self.options.long_break_color,
-                };
+                });
 
                 let layout = Layout::default()
                     .direction(Direction::Vertical)
@@ -195,7 +256,7 @@ impl Ui {
                         Constraint::Percentage(20), // Top empty space.
                         Constraint::Percentage(35), // Timer.
                         Constraint::Percentage(25), // Progress bar.
-                        Constraint::Percentage(20), // Bottom empty space.
+                        Constraint::Percentage(20), // Notice.
                     ])
                     .split(frame.size());
 
@@ -204,44 +265,126 @@ impl Ui {
                     .style(Style::default().fg(color.into()))
                     .alignment(Alignment::Center);
 
+                let activity = match &self.label {
+                    Some(label) => format!("{} ({})", label, self.timer_data.activity),
+                    None => self.timer_data.activity.to_string(),
+                };
+                // Tally marks of interruptions, if any.
+                let activity = match self.interruptions {
+                    interruptions if interruptions == Interruptions::default() => activity,
+                    interruptions => format!("{activity} {interruptions}"),
+                };
+                let activity = match self.screen {
+                    Screen::Paused(paused) if paused.duration().is_zero() => {
+                        format!("{activity} (paused)")
+                    }
+                    Screen::Paused(paused) => format!("{activity} (paused for {paused})"),
+                    _ => activity,
+                };
+
+                // Full progress bar in overtime color, inviting to move on.
+                let (title, gauge_style) = if self.timer_data.overtime {
+                    (
+                        format!("{activity} overtime (press `s` to move on)"),
+                        Style::default()
+                            .fg(self.options.overtime_color.into())
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    (
+                        activity,
+                        Style::default()
+                            .fg(color.into())
+                            .bg(self.options.background_color.into()),
+                    )
+                };
+
                 let progress_bar = Gauge::default()
                     .block(
                         Block::default()
                             .borders(Borders::NONE)
                             .title_alignment(Alignment::Left)
-                            .title(self.timer_data.activity.to_string())
+                            .title(title)
                             .border_style(Style::default().add_modifier(Modifier::BOLD)),
                     )
-                    .gauge_style(
-                        Style::default()
-                            .fg(color.into())
-                            .bg(self.options.background_color.into()),
-                    )
+                    .gauge_style(gauge_style)
                     .percent(self.timer_data.perc);
 
                 // Render widgets!
+                // Goal and task right above the timer.
+                let top = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Min(0),
+                        Constraint::Length(1), // Goal.
+                        Constraint::Length(1), // Task.
+                    ])
+                    .split(layout[0]);
+                if let Some(goal) = &self.goal {
+                    let goal = Paragraph::new(goal.as_ref())
+                        .block(Block::default().borders(Borders::NONE))
+                        .alignment(Alignment::Center);
+                    frame.render_widget(goal, top[1]);
+                }
+                if let Some(task) = &self.task {
+                    let task = Paragraph::new(task.as_ref())
+                        .block(Block::default().borders(Borders::NONE))
+                        .style(Style::default().add_modifier(Modifier::BOLD))
+                        .alignment(Alignment::Center);
+                    frame.render_widget(task, top[2]);
+                }
                 frame.render_widget(timer, layout[1]);
                 frame.render_widget(progress_bar, layout[2]);
+                // Task prompt takes precedence over notices.
+                if let Some(notice) = self.prompt().as_ref().or(self.notice.as_ref()) {
+                    let notice = Paragraph::new(notice.as_ref())
+                        .block(Block::default().borders(Borders::NONE))
+                        .alignment(Alignment::Center);
+                    frame.render_widget(notice, layout[3]);
+                }
             })
             .map_err(Error::Terminal)?;
 
         Ok(())
     }
 
-    /// Render expired screen.
-    fn render_expired(&self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+    /// Render expired screen, along with `next` activity if waiting for the user to start it.
+    fn render_expired(
+        &self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+        next: Option<Activity>,
+    ) -> Result<()> {
         terminal
             .draw(|frame| {
                 let layout = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints([
-                        Constraint::Percentage(45), // Top empty space.
-                        Constraint::Percentage(10), // Timer.
-                        Constraint::Percentage(45), // Bottom empty space.
+                        Constraint::Percentage(35), // Top empty space.
+                        Constraint::Percentage(30), // Timer.
+                        Constraint::Percentage(35), // Bottom empty space.
                     ])
                     .split(frame.size());
 
-                let text = Paragraph::new("Timer expired")
+                let text = match next {
+                    // Breaks can be skipped.
+                    Some(next @ Activity::Pomodoro(_)) => {
+                        format!("Timer expired\n\nNext: {next}\n[Enter] start  [b] back  [q] quit")
+                    }
+                    Some(next) => {
+                        format!(
+                            "Timer expired\n\nNext: {next}\n[Enter] start  [s] skip  [b] back  [q] quit"
+                        )
+                    }
+                    None => "Timer expired".to_string(),
+                };
+                // Task can be set before starting next activity.
+                let text = match (next, self.prompt(), &self.task) {
+                    (None, ..) => text,
+                    (_, Some(prompt), _) => format!("{text}\n\n{prompt}"),
+                    (_, None, Some(task)) => format!("{text}\n\nTask: {task}  [t] change"),
+                    (_, None, None) => format!("{text}\n\n[t] set task"),
+                };
+                let text = Paragraph::new(text)
                     .block(Block::default().borders(Borders::NONE))
                     .alignment(Alignment::Center);
 
@@ -255,13 +398,70 @@ impl Ui {
     fn draw_screen(&self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
         match self.screen {
             Screen::Running => self.render_timer(terminal)?,
-            Screen::Paused => self.render_timer(terminal)?,
-            Screen::Expired => self.render_expired(terminal)?,
+            Screen::Paused(_) => self.render_timer(terminal)?,
+            Screen::Expired => self.render_expired(terminal, None)?,
+            Screen::Waiting(next) => self.render_expired(terminal, Some(next))?,
         }
 
         Ok(())
     }
 
+    /// Update timer data with `activity`'s `timer`.
+    fn update_timer_data(&mut self, activity: Activity, timer: Timer) {
+        // Notices are relative to the activity they were sent for, or to the first one if sent
+        // before any activity started (`Pomodoro(0)`).
+        if activity != self.timer_data.activity && self.timer_data.activity != Activity::Pomodoro(0)
+        {
+            self.notice = None;
+        }
+        self.timer_data = TimerData::new(
+            activity,
+            timer.to_figlet(&self.options.font),
+            timer.remaining_percentage(),
+            false,
+        );
+    }
+
+    /// Update [`Ui`] state according to [`UiCommand`].
+    fn update(&mut self, ui_command: UiCommand) {
+        match ui_command {
+            UiCommand::Draw(timer_status) => {
+                // Update current screen.
+                self.screen = match timer_status {
+                    TimerStatus::Running(activity, timer) => {
+                        self.update_timer_data(activity, timer);
+                        Screen::Running
+                    }
+                    TimerStatus::Paused(activity, timer, paused) => {
+                        self.update_timer_data(activity, timer);
+                        Screen::Paused(paused)
+                    }
+                    TimerStatus::Overtime(activity, timer) => {
+                        self.timer_data = TimerData::new(
+                            activity,
+                            timer.overtime_figlet(&self.options.font),
+                            100.0,
+                            true,
+                        );
+                        Screen::Running
+                    }
+                    TimerStatus::Expired => Screen::Expired,
+                    TimerStatus::Waiting(next) => Screen::Waiting(next),
+                };
+            }
+            UiCommand::Notice(notice) => self.notice = Some(notice),
+            UiCommand::Goal(goal) => self.goal = Some(goal),
+            UiCommand::Step { label, color } => {
+                self.label = label;
+                self.color = color;
+            }
+            UiCommand::Task(task) => self.task = task,
+            UiCommand::Input(input) => self.input = input,
+            UiCommand::Interruptions(interruptions) => self.interruptions = interruptions,
+            UiCommand::Refresh => {}
+        }
+    }
+
     /// Spawn thread listening for [`UiCommand`]s.
     pub fn spawn_thread(
         mut self,
@@ -271,28 +471,14 @@ impl Ui {
         let mut terminal = setup_terminal().map_err(Error::Terminal)?;
 
         Ok(thread::spawn(move || {
-            for ui_command in rx {
-                match ui_command {
-                    UiCommand::Draw(timer_status) => {
-                        // Update current screen.
-                        self.screen = match timer_status {
-                            TimerStatus::Running(activity, timer) => {
-                                // Update timer data.
-                                self.timer_data = TimerData::new(
-                                    activity,
-                                    timer.to_figlet(&self.options.font),
-                                    timer.remaining_percentage(),
-                                );
-                                Screen::Running
-                            }
-                            TimerStatus::Paused => Screen::Paused,
-                            TimerStatus::Expired => Screen::Expired,
-                        };
-                        // Draw the screen
-                        self.draw_screen(&mut terminal)?;
-                    }
-                    UiCommand::Refresh => self.draw_screen(&mut terminal)?,
+            while let Ok(ui_command) = rx.recv() {
+                self.update(ui_command);
+                // Skip stale frames if rendering lags behind the timer: apply all queued commands
+                // and draw the screen only once.
+                for ui_command in rx.try_iter() {
+                    self.update(ui_command);
                 }
+                self.draw_screen(&mut terminal)?;
             }
 
             // Restore terminal to previous screen and behaviour.
@@ -302,3 +488,84 @@ impl Ui {
         }))
     }
 }
+
+/// Log of [`UiCommand`]s, in place of the [`Ui`] with no terminal.
+#[derive(Debug, Default)]
+struct Log {
+    /// Last timer status logged.
+    last: Option<String>,
+}
+
+impl Log {
+    /// Message to log for `ui_command`, if any: timer status changes and notices only.
+    fn message(&mut self, ui_command: UiCommand) -> Option<String> {
+        match ui_command {
+            UiCommand::Draw(timer_status) => {
+                let status = match timer_status {
+                    TimerStatus::Running(activity, _) => format!("{activity} running"),
+                    TimerStatus::Paused(activity, _, _) => format!("{activity} paused"),
+                    TimerStatus::Overtime(activity, _) => format!("{activity} in overtime"),
+                    TimerStatus::Expired => "Timer expired".to_string(),
+                    TimerStatus::Waiting(next) => {
+                        format!("Waiting to start {next} (`solanum ctl resume`)")
+                    }
+                };
+                if self.last.as_ref() == Some(&status) {
+                    return None;
+                }
+                self.last = Some(status.clone());
+                Some(status)
+            }
+            UiCommand::Notice(notice) => Some(notice),
+            UiCommand::Goal(goal) => Some(goal),
+            _ => None,
+        }
+    }
+}
+
+/// Spawn thread logging [`UiCommand`]s to standard output, in place of the [`Ui`] thread.
+pub fn spawn_log_thread(rx: Receiver<UiCommand>) -> thread::JoinHandle<Result<()>> {
+    thread::spawn(move || {
+        let mut log = Log::default();
+        for ui_command in rx {
+            if let Some(message) = log.message(ui_command) {
+                // Standard output may be closed when running in the background.
+                let _ = writeln!(
+                    io::stdout(),
+                    "[{}] {message}",
+                    Local::now().format("%H:%M:%S")
+                );
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    /// Timer status logged only when changed.
+    fn log_messages() {
+        let mut log = Log::default();
+        let running = |secs| {
+            UiCommand::Draw(TimerStatus::Running(
+                Activity::Pomodoro(1),
+                Timer::new(0, 0, secs),
+            ))
+        };
+        assert_eq!(
+            log.message(running(60)).as_deref(),
+            Some("Pomodoro #1 running")
+        );
+        assert_eq!(log.message(running(59)), None);
+        assert_eq!(log.message(UiCommand::Refresh), None);
+        assert_eq!(
+            log.message(UiCommand::Draw(TimerStatus::Expired))
+                .as_deref(),
+            Some("Timer expired")
+        );
+    }
+}